@@ -0,0 +1,64 @@
+use std::{
+    io,
+    process::{Child, Command, Stdio},
+};
+
+/// Keeps the system from going to sleep for as long as it is alive
+///
+/// This holds on to a platform helper process (`systemd-inhibit` on Linux, `caffeinate` on
+/// macOS) which is killed when the inhibitor is dropped.
+pub struct Inhibitor {
+    child: Child,
+}
+
+impl Inhibitor {
+    /// Start inhibiting sleep
+    pub fn acquire() -> io::Result<Self> {
+        let child = inhibit_command()?
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        Ok(Inhibitor { child })
+    }
+
+    /// Whether the helper process is still running, and so still inhibiting sleep
+    pub fn is_held(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+}
+
+impl Drop for Inhibitor {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn inhibit_command() -> io::Result<Command> {
+    let mut command = Command::new("systemd-inhibit");
+    command.args([
+        "--what=idle:sleep",
+        "--who=timers",
+        "--why=A timer is running",
+        "sleep",
+        "infinity",
+    ]);
+    Ok(command)
+}
+
+#[cfg(target_os = "macos")]
+fn inhibit_command() -> io::Result<Command> {
+    let mut command = Command::new("caffeinate");
+    command.arg("-di");
+    Ok(command)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn inhibit_command() -> io::Result<Command> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "keeping the system awake is not supported on this platform",
+    ))
+}
@@ -0,0 +1,151 @@
+//! Short codes that carry a timer's setup from one command line to another
+//!
+//! A code is the [`Setup`] as JSON behind a version byte, in URL-safe base64 without padding, so
+//! it can be pasted into a chat or a URL and launched with `--code`. Codes from a newer version
+//! that this one can't read are rejected rather than half understood.
+
+use crate::config::ThemeConfig;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, DecodeError, Engine};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// The version byte every code starts with, bumped whenever [`Setup`] changes incompatibly
+const VERSION: u8 = 1;
+
+/// Everything about a timer that is worth sharing, leaving out anything tied to one machine such
+/// as sounds and files
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Setup {
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "millis")]
+    pub countdown: Option<Duration>,
+    /// Where a stopwatch starts from
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "millis")]
+    pub elapsed: Option<Duration>,
+    /// Show the time of day instead of a timer
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub clock: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Timers stacked in place of the single one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub segments: Vec<SetupSegment>,
+    /// The colors the timer was drawn in, so they don't depend on the themes the other side has
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<ThemeConfig>,
+}
+
+/// One of several stacked timers within a [`Setup`]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SetupSegment {
+    pub label: String,
+    /// A countdown from this long, or a stopwatch without it
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "millis")]
+    pub countdown: Option<Duration>,
+}
+
+impl Setup {
+    /// The code to launch this setup with
+    pub fn encode(&self) -> String {
+        let mut bytes = vec![VERSION];
+        bytes.extend(serde_json::to_vec(self).unwrap_or_default());
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    /// The setup a code was made from, or what is wrong with it
+    pub fn decode(code: &str) -> Result<Setup, String> {
+        let code = code.trim();
+        let bytes = URL_SAFE_NO_PAD.decode(code).map_err(|error| match error {
+            DecodeError::InvalidByte(offset, _) => {
+                match code.get(offset..).and_then(|rest| rest.chars().next()) {
+                    Some(c) => format!("`{c}` can't appear in a code"),
+                    None => "the code is damaged".to_string(),
+                }
+            }
+            _ => "the code has been cut short".to_string(),
+        })?;
+        let (&version, json) = bytes.split_first().ok_or("the code is empty")?;
+        if version != VERSION {
+            return Err(format!(
+                "the code is from version {version} of the format, but only version {VERSION} \
+                 can be read"
+            ));
+        }
+        let setup: Setup = serde_json::from_slice(json)
+            .map_err(|error| format!("the code is damaged or incomplete: {error}"))?;
+        if let Some(theme) = &setup.theme {
+            theme
+                .theme()
+                .map_err(|error| format!("the code has an {error}"))?;
+        }
+        Ok(setup)
+    }
+}
+
+/// An optional duration as a whole number of milliseconds, which is all a code needs to keep
+mod millis {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => serializer.serialize_some(&(duration.as_millis() as u64)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<u64>::deserialize(deserializer)?.map(Duration::from_millis))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn setups_survive_the_trip_through_a_code() {
+        let setup = Setup {
+            countdown: Some(Duration::from_millis(1_500_250)),
+            label: Some("tea, green".to_string()),
+            segments: vec![SetupSegment {
+                label: "steep".to_string(),
+                countdown: Some(Duration::from_secs(180)),
+            }],
+            theme: Some(ThemeConfig {
+                fg: "#222222".to_string(),
+                bg: "white".to_string(),
+                alarm: Some("red".to_string()),
+            }),
+            ..Setup::default()
+        };
+        let code = setup.encode();
+        assert!(code
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(Setup::decode(&code), Ok(setup));
+    }
+
+    #[test]
+    fn broken_codes_say_what_is_wrong() {
+        assert!(Setup::decode("abc+").unwrap_err().contains("`+`"));
+        assert!(Setup::decode(&URL_SAFE_NO_PAD.encode(b"\x01{\"clock\":tr"))
+            .unwrap_err()
+            .contains("damaged or incomplete"));
+        assert!(Setup::decode("a").unwrap_err().contains("cut short"));
+        assert!(Setup::decode(&URL_SAFE_NO_PAD.encode(b"\x07{}"))
+            .unwrap_err()
+            .contains("version 7"));
+        let theme = r#"{"theme":{"fg":"white","bg":"chartreuse-ish"}}"#;
+        let bad_color = URL_SAFE_NO_PAD.encode([&[VERSION], theme.as_bytes()].concat());
+        assert!(Setup::decode(&bad_color)
+            .unwrap_err()
+            .contains("invalid bg color"));
+    }
+}
@@ -0,0 +1,87 @@
+use crate::tui::Output;
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+/// Something to do when a countdown finishes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CompletionAction {
+    /// Ring the terminal bell, on the stream the TUI is drawn on so it never ends up in the
+    /// results printed to stdout
+    Bell,
+    /// Show a desktop notification with the given message
+    Notify(String),
+    /// Run a shell command
+    Command(String),
+    /// Play a sound file
+    Sound(PathBuf),
+}
+
+impl CompletionAction {
+    /// Carry out the action without waiting for any process it starts
+    pub fn run(&self) -> io::Result<()> {
+        match self {
+            CompletionAction::Bell => {
+                Output.write_all(b"\x07")?;
+                Output.flush()
+            }
+            CompletionAction::Notify(message) => spawn(notify_command(message)),
+            CompletionAction::Command(command) => {
+                let mut shell = Command::new("sh");
+                shell.arg("-c").arg(command);
+                spawn(shell)
+            }
+            CompletionAction::Sound(path) => spawn(sound_command(path)),
+        }
+    }
+
+    /// A short name for the action, used when reporting that it failed
+    pub fn name(&self) -> &'static str {
+        match self {
+            CompletionAction::Bell => "bell",
+            CompletionAction::Notify(_) => "notify",
+            CompletionAction::Command(_) => "on-finish",
+            CompletionAction::Sound(_) => "sound",
+        }
+    }
+}
+
+fn spawn(mut command: Command) -> io::Result<()> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
+
+#[cfg(target_os = "macos")]
+fn notify_command(message: &str) -> Command {
+    let script = format!("display notification {message:?} with title \"timers\"");
+    let mut command = Command::new("osascript");
+    command.arg("-e").arg(script);
+    command
+}
+
+#[cfg(not(target_os = "macos"))]
+fn notify_command(message: &str) -> Command {
+    let mut command = Command::new("notify-send");
+    command.arg("timers").arg(message);
+    command
+}
+
+#[cfg(target_os = "macos")]
+fn sound_command(path: &std::path::Path) -> Command {
+    let mut command = Command::new("afplay");
+    command.arg(path);
+    command
+}
+
+#[cfg(not(target_os = "macos"))]
+fn sound_command(path: &std::path::Path) -> Command {
+    let mut command = Command::new("paplay");
+    command.arg(path);
+    command
+}
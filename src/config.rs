@@ -0,0 +1,324 @@
+use crate::{duration::parse_duration, Phase, Theme, Workout};
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Settings read from `config.toml`
+///
+/// ```toml
+/// bold = true
+///
+/// [sounds]
+/// lap = "/usr/share/sounds/freedesktop/stereo/bell.oga"
+///
+/// [themes.paper]
+/// fg = "#222222"
+/// bg = "white"
+/// alarm = "red"
+///
+/// [workouts.tabata]
+/// repeat = 8
+/// phases = [
+///     { name = "work", duration = "20s", color = "red", sound = "/usr/share/sounds/go.oga" },
+///     { name = "rest", duration = "10s", color = "green" },
+/// ]
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Draw the digits in bold, as `--bold` does
+    #[serde(default)]
+    pub bold: bool,
+    /// Draw the digits in italics, as `--italic` does
+    #[serde(default)]
+    pub italic: bool,
+    /// Themes that can be picked with `--theme` alongside the built in ones, overriding any
+    /// built in theme with the same name
+    #[serde(default)]
+    pub themes: BTreeMap<String, ThemeConfig>,
+    #[serde(default)]
+    pub sounds: Sounds,
+    /// Workouts that can be run with `--workout`, each a list of timed phases
+    #[serde(default)]
+    pub workouts: BTreeMap<String, WorkoutConfig>,
+}
+
+/// Sound files to play as things happen to the timer; any left out are silent
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Sounds {
+    /// The timer was started or resumed
+    pub start: Option<PathBuf>,
+    /// The timer was paused
+    pub pause: Option<PathBuf>,
+    /// A lap was recorded by hand
+    pub lap: Option<PathBuf>,
+    /// A countdown finished
+    pub finish: Option<PathBuf>,
+}
+
+/// A theme as written in the config file, with colors in any form `--fg` accepts
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThemeConfig {
+    pub fg: String,
+    pub bg: String,
+    /// Falls back to the default theme's alarm color
+    #[serde(default)]
+    pub alarm: Option<String>,
+}
+
+impl ThemeConfig {
+    /// The theme these colors describe, or which of them isn't a color
+    pub fn theme(&self) -> Result<Theme, String> {
+        let color = |field: &str, value: &str| {
+            Color::from_str(value).map_err(|_| format!("invalid {field} color `{value}`"))
+        };
+        Ok(Theme {
+            fg: color("fg", &self.fg)?,
+            bg: color("bg", &self.bg)?,
+            alarm: match &self.alarm {
+                Some(alarm) => color("alarm", alarm)?,
+                None => Theme::default().alarm,
+            },
+        })
+    }
+}
+
+/// A workout as written in the config file
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkoutConfig {
+    /// How many times to go through the phases
+    #[serde(default = "once")]
+    pub repeat: u32,
+    pub phases: Vec<PhaseConfig>,
+}
+
+fn once() -> u32 {
+    1
+}
+
+/// A single phase of a [`WorkoutConfig`], with its duration in any form `--countdown` accepts
+/// and its color in any form `--fg` does
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PhaseConfig {
+    pub name: String,
+    pub duration: String,
+    /// The color of the digits during the phase, or the theme's if left out
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Played as the phase starts, after the one before it finishes
+    #[serde(default)]
+    pub sound: Option<PathBuf>,
+}
+
+impl WorkoutConfig {
+    /// The workout these phases describe, repeated as many times as asked, or which phase is
+    /// wrong
+    pub fn workout(&self) -> Result<Workout, String> {
+        if self.phases.is_empty() || self.repeat == 0 {
+            return Err("has no phases to run".to_string());
+        }
+        let phases = self
+            .phases
+            .iter()
+            .map(|phase| {
+                let invalid = |error: String| format!("phase `{}`: {error}", phase.name);
+                let duration = parse_duration(&phase.duration).map_err(invalid)?;
+                if duration.is_zero() {
+                    return Err(invalid("duration must be longer than zero".to_string()));
+                }
+                let color = match &phase.color {
+                    Some(color) => Some(
+                        Color::from_str(color)
+                            .map_err(|_| invalid(format!("invalid color `{color}`")))?,
+                    ),
+                    None => None,
+                };
+                Ok(Phase {
+                    name: phase.name.clone(),
+                    duration,
+                    color,
+                    sound: phase.sound.clone(),
+                })
+            })
+            .collect::<Result<Vec<Phase>, String>>()?;
+        Ok(Workout {
+            phases,
+            repeat: self.repeat,
+        })
+    }
+}
+
+impl Config {
+    /// Read the config file, or `None` if there isn't one, checking every theme's colors
+    pub fn load(path: &Path) -> io::Result<Option<Config>> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        let invalid = |message: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {message}", path.display()),
+            )
+        };
+        let config: Config =
+            toml::from_str(&contents).map_err(|error| invalid(error.to_string()))?;
+        for (name, theme) in &config.themes {
+            theme
+                .theme()
+                .map_err(|error| invalid(format!("theme `{name}`: {error}")))?;
+        }
+        for (name, workout) in &config.workouts {
+            workout
+                .workout()
+                .map_err(|error| invalid(format!("workout `{name}` {error}")))?;
+        }
+        Ok(Some(config))
+    }
+
+    /// Look up a theme by name, preferring one from the config file over a built in one
+    pub fn theme(&self, name: &str) -> Option<Theme> {
+        match self.themes.get(name) {
+            Some(theme) => theme.theme().ok(),
+            None => Theme::preset(name),
+        }
+    }
+
+    /// Every theme that can be picked, built in ones first, with custom ones replacing any they
+    /// share a name with
+    pub fn all_themes(&self) -> Vec<(String, Theme)> {
+        let built_in = Theme::PRESETS
+            .iter()
+            .filter(|(name, _)| !self.themes.contains_key(*name))
+            .map(|&(name, theme)| (name.to_string(), theme));
+        let custom = self
+            .themes
+            .iter()
+            .filter_map(|(name, theme)| Some((name.clone(), theme.theme().ok()?)));
+        built_in.chain(custom).collect()
+    }
+}
+
+/// The directory the config file lives in: `$XDG_CONFIG_HOME/timers`, falling back to
+/// `~/.config/timers`
+pub fn config_dir() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("timers"))
+}
+
+/// Where the config file is read from unless another is given
+pub fn default_path() -> Option<PathBuf> {
+    Some(config_dir()?.join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_themes_join_and_override_the_built_in_ones() {
+        let config: Config = toml::from_str(
+            r##"
+            [themes.paper]
+            fg = "#222222"
+            bg = "white"
+
+            [themes.matrix]
+            fg = "lightgreen"
+            bg = "black"
+            alarm = "yellow"
+            "##,
+        )
+        .unwrap();
+
+        let paper = config.theme("paper").unwrap();
+        assert_eq!(paper.fg, Color::Rgb(0x22, 0x22, 0x22));
+        assert_eq!(paper.alarm, Theme::default().alarm);
+        assert_eq!(config.theme("matrix").unwrap().alarm, Color::Yellow);
+        assert_eq!(
+            config.theme("nord").unwrap().bg,
+            Theme::preset("nord").unwrap().bg
+        );
+
+        let names: Vec<String> = config
+            .all_themes()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names.iter().filter(|name| *name == "matrix").count(), 1);
+        assert!(names.contains(&"paper".to_string()));
+    }
+
+    #[test]
+    fn digit_styles_default_to_off() {
+        let config: Config = toml::from_str("italic = true\n").unwrap();
+        assert!(config.italic);
+        assert!(!config.bold);
+        assert_eq!(toml::from_str::<Config>("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn invalid_colors_name_the_theme() {
+        let path = env::temp_dir().join(format!("timers-config-{}.toml", std::process::id()));
+        fs::write(
+            &path,
+            "[themes.broken]\nfg = \"white\"\nbg = \"chartreuse-ish\"\n",
+        )
+        .unwrap();
+        let error = Config::load(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+
+        let message = error.to_string();
+        assert!(message.contains("theme `broken`"), "{message}");
+        assert!(
+            message.contains("invalid bg color `chartreuse-ish`"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn workouts_repeat_their_phases_in_order() {
+        let config: Config = toml::from_str(
+            r#"
+            [workouts.tabata]
+            repeat = 2
+            phases = [
+                { name = "work", duration = "20s", color = "red", sound = "go.oga" },
+                { name = "rest", duration = "10s" },
+            ]
+
+            [workouts.broken]
+            phases = [{ name = "plank", duration = "forever" }]
+            "#,
+        )
+        .unwrap();
+
+        let workout = config.workouts["tabata"].workout().unwrap();
+        let names: Vec<&str> = (0..workout.len())
+            .filter_map(|index| workout.phase(index))
+            .map(|phase| phase.name.as_str())
+            .collect();
+        assert_eq!(names, ["work", "rest", "work", "rest"]);
+        let third = workout.phase(2).unwrap();
+        assert_eq!(third.duration, std::time::Duration::from_secs(20));
+        assert_eq!(third.color, Some(Color::Red));
+        assert_eq!(third.sound, Some(PathBuf::from("go.oga")));
+        assert_eq!(workout.phase(1).unwrap().color, None);
+
+        let error = config.workouts["broken"].workout().unwrap_err();
+        assert!(error.starts_with("phase `plank`"), "{error}");
+    }
+}
@@ -0,0 +1,211 @@
+use crate::{duration::parse_duration, Action};
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+/// A named pipe that [`Action`]s can be written to as newline separated commands
+///
+/// A background thread reads commands such as `toggle` or `set 5m` from the pipe and sends
+/// them down a channel, which is handed to [`crate::Timers::with_commands`]. Dropping the pipe
+/// tells the thread to stop and removes the pipe from the filesystem.
+pub struct ControlPipe {
+    path: PathBuf,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ControlPipe {
+    /// Create the pipe at `path` and start reading from it, returning the commands read
+    ///
+    /// A pipe left behind at `path` by a process that is gone is replaced.
+    pub fn create(path: &Path) -> io::Result<(ControlPipe, Receiver<Action>)> {
+        match make_fifo(path) {
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists && is_stale(path) => {
+                fs::remove_file(path)?;
+                make_fifo(path)?;
+            }
+            result => result?,
+        }
+        let (sender, receiver) = mpsc::channel();
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let handle = thread::spawn({
+            let path = path.to_path_buf();
+            let shutdown = Arc::clone(&shutdown);
+            move || {
+                while !shutdown.load(Ordering::Relaxed) {
+                    // Opening blocks until something opens the pipe for writing, and reading
+                    // ends once every writer has closed it, so keep reopening it
+                    let Ok(pipe) = File::open(&path) else {
+                        break;
+                    };
+                    for line in BufReader::new(pipe).lines() {
+                        let Ok(line) = line else {
+                            break;
+                        };
+                        if let Ok(action) = parse_command(&line) {
+                            if sender.send(action).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        let pipe = ControlPipe {
+            path: path.to_path_buf(),
+            shutdown,
+            handle: Some(handle),
+        };
+        Ok((pipe, receiver))
+    }
+}
+
+impl Drop for ControlPipe {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            // A reader waiting for a writer wakes up to notice the shutdown when the pipe is
+            // opened for writing here. One reading from a writer that still has the pipe open
+            // can't be woken, so it is left to end with the process rather than waited for.
+            if !handle.is_finished() {
+                wake_reader(&self.path);
+            }
+        }
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Parse a single command such as `toggle`, `lap` or `set 1m30s`
+pub fn parse_command(line: &str) -> Result<Action, String> {
+    let line = line.trim();
+    let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+    let action = match command {
+        "toggle" => Action::Toggle,
+        "reset" => Action::Reset,
+        "lap" => Action::Lap,
+        "set" => Action::Set(parse_duration(argument)?),
+        "next" => Action::SelectNext,
+        "previous" => Action::SelectPrevious,
+        "pause-all" => Action::PauseAll,
+        "invert" => Action::Invert,
+        "mode" => Action::SwitchMode,
+        "collapse" => Action::Collapse,
+        "banners" => Action::Banners,
+        "detach" => Action::Detach,
+        "save" => Action::SaveSlot(parse_slot(argument)?),
+        "load" => Action::LoadSlot(parse_slot(argument)?),
+        "quit" => Action::Quit,
+        _ => return Err(format!("unknown command `{line}`")),
+    };
+    Ok(action)
+}
+
+/// A save slot number from 1 to 9
+fn parse_slot(argument: &str) -> Result<u8, String> {
+    match argument.trim().parse() {
+        Ok(slot @ 1..=9) => Ok(slot),
+        _ => Err(format!("invalid slot `{argument}`, expected 1 to 9")),
+    }
+}
+
+#[cfg(unix)]
+fn make_fifo(path: &Path) -> io::Result<()> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+    // SAFETY: `path` is a valid NUL terminated string that outlives the call
+    if unsafe { libc::mkfifo(path.as_ptr(), 0o600) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(unix))]
+fn make_fifo(_path: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "control pipes are not supported on this platform",
+    ))
+}
+
+/// Whether anything has the pipe at `path` open for reading, so that commands written to it
+/// would be read
+#[cfg(unix)]
+pub(crate) fn has_reader(path: &Path) -> bool {
+    use std::{fs::OpenOptions, os::unix::fs::OpenOptionsExt};
+
+    // Opening a pipe for writing without blocking fails straight away when nothing reads it
+    match OpenOptions::new()
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(path)
+    {
+        Ok(_) => true,
+        Err(error) => error.raw_os_error() != Some(libc::ENXIO) && path.exists(),
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn has_reader(path: &Path) -> bool {
+    path.exists()
+}
+
+/// Whether `path` is a pipe that nothing reads any more, left behind by a process that is gone
+#[cfg(unix)]
+fn is_stale(path: &Path) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+
+    fs::symlink_metadata(path).is_ok_and(|metadata| metadata.file_type().is_fifo())
+        && !has_reader(path)
+}
+
+#[cfg(not(unix))]
+fn is_stale(_path: &Path) -> bool {
+    false
+}
+
+/// Open the pipe at `path` for writing and close it again, without waiting for a reader
+#[cfg(unix)]
+fn wake_reader(path: &Path) {
+    use std::{fs::OpenOptions, os::unix::fs::OpenOptionsExt};
+
+    let _ = OpenOptions::new()
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(path);
+}
+
+#[cfg(not(unix))]
+fn wake_reader(_path: &Path) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn commands_map_to_actions() {
+        assert_eq!(parse_command("toggle"), Ok(Action::Toggle));
+        assert_eq!(parse_command("  lap \n"), Ok(Action::Lap));
+        assert_eq!(
+            parse_command("set 5m"),
+            Ok(Action::Set(Duration::from_secs(300)))
+        );
+        assert!(parse_command("set").is_err());
+        assert_eq!(parse_command("save 2"), Ok(Action::SaveSlot(2)));
+        assert!(parse_command("load 10").is_err());
+        assert!(parse_command("launch").is_err());
+    }
+}
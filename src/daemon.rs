@@ -0,0 +1,262 @@
+//! Handing timers back and forth between the TUI and a process running them in the background
+//!
+//! A background timer is a `timers` process started with `--daemon`. It keeps
+//! [`state_path`] up to date with a [`Handoff`] of its timers and reads commands from a
+//! [`crate::control::ControlPipe`] at [`control_path`]. Attaching sends it `quit` down that pipe;
+//! it writes a last handoff and removes the pipe as it exits, and the TUI picks the timers up from
+//! the file, catching up on the time that passed in between. Detaching from the TUI writes a
+//! handoff and starts a new background process to pick it up in turn.
+
+use crate::{
+    control,
+    session::{self, TimerSummary},
+    Mode, TimerSettings,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::OsString,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// How long attaching waits for the background process to answer and hand its timers over
+const HANDOFF_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How often attaching checks whether the background process has finished handing over
+const HANDOFF_POLL: Duration = Duration::from_millis(20);
+
+/// Every timer as it was at `saved_at`, for another process to carry on with
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Handoff {
+    pub saved_at: SystemTime,
+    pub timers: Vec<HandoffTimer>,
+    /// Index into `timers` of the one key presses act on
+    pub selected: usize,
+    /// Whether every timer is being held by pause all
+    pub paused_all: bool,
+}
+
+/// A single timer within a [`Handoff`]: its summary, along with what else it takes to set the
+/// same timer up again
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HandoffTimer {
+    #[serde(flatten)]
+    pub summary: TimerSummary,
+    /// What the timer measures in full, where the summary only names it; a countdown being run
+    /// over with `--then-stopwatch` is still given as the countdown
+    pub measuring: Mode,
+    /// How much of the lead-in is still to go
+    pub lead_in_left: Duration,
+    pub settings: TimerSettings,
+}
+
+impl Handoff {
+    /// Read a handoff, or `None` if there isn't one
+    pub fn load(path: &Path) -> io::Result<Option<Handoff>> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// Write the handoff, creating the parent directory if needed
+    ///
+    /// It is written to a temporary file first and moved into place, so a reader never sees
+    /// half of it.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let partial = path.with_extension("partial");
+        fs::write(&partial, serde_json::to_string_pretty(self)?)?;
+        fs::rename(partial, path)
+    }
+
+    /// How long ago the handoff was written
+    pub fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.saved_at)
+            .unwrap_or(Duration::ZERO)
+    }
+}
+
+/// The directory a background timer keeps its files in, under [`session::state_dir`]
+pub fn daemon_dir() -> Option<PathBuf> {
+    Some(session::state_dir()?.join("daemon"))
+}
+
+/// Where a background timer keeps its latest [`Handoff`]
+pub fn state_path() -> Option<PathBuf> {
+    Some(daemon_dir()?.join("state.json"))
+}
+
+/// Where a background timer reads commands from
+pub fn control_path() -> Option<PathBuf> {
+    Some(daemon_dir()?.join("control"))
+}
+
+/// Whether a background timer is running, going by whether anything reads its control pipe
+///
+/// A pipe left behind by a background timer that crashed doesn't count.
+pub fn is_running(control: &Path) -> bool {
+    control::has_reader(control)
+}
+
+/// Start this program again in the background with `args`, detached from the terminal
+#[cfg(unix)]
+pub fn spawn(args: impl IntoIterator<Item = OsString>) -> io::Result<()> {
+    use std::{
+        env,
+        os::unix::process::CommandExt,
+        process::{Command, Stdio},
+    };
+
+    let mut command = Command::new(env::current_exe()?);
+    command
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        // Its own process group, so signals sent to the terminal's foreground job miss it
+        .process_group(0);
+    command.spawn().map(|_| ())
+}
+
+#[cfg(not(unix))]
+pub fn spawn(_args: impl IntoIterator<Item = OsString>) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "running timers in the background is not supported on this platform",
+    ))
+}
+
+/// Ask the background timer to hand its timers over and exit, returning them
+pub fn request_handoff(control: &Path, state: &Path) -> io::Result<Handoff> {
+    if !is_running(control) {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no timer is running in the background",
+        ));
+    }
+
+    // Opening a pipe for writing blocks until it is opened for reading, which never happens if
+    // the process that made it is gone, so give up on it after a while
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn({
+        let control = control.to_path_buf();
+        move || {
+            let sent = OpenOptions::new()
+                .write(true)
+                .open(&control)
+                .and_then(|mut pipe| pipe.write_all(b"quit\n"));
+            let _ = sender.send(sent);
+        }
+    });
+    let not_answering = || {
+        io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!(
+                "the background timer isn't answering; remove {} if it is no longer running",
+                control.display()
+            ),
+        )
+    };
+    receiver
+        .recv_timeout(HANDOFF_TIMEOUT)
+        .map_err(|_| not_answering())??;
+
+    // The pipe is removed once the last handoff has been written, though it stops being read
+    // before that
+    let deadline = Instant::now() + HANDOFF_TIMEOUT;
+    while control.exists() {
+        if Instant::now() >= deadline {
+            return Err(not_answering());
+        }
+        thread::sleep(HANDOFF_POLL);
+    }
+    take(state)?.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "the background timer exited without handing over its timers",
+        )
+    })
+}
+
+/// Read the handoff at `path` and remove it, so the same timers aren't picked up twice
+pub fn take(path: &Path) -> io::Result<Option<Handoff>> {
+    let handoff = Handoff::load(path)?;
+    if handoff.is_some() {
+        fs::remove_file(path)?;
+    }
+    Ok(handoff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handoffs_are_taken_only_once() {
+        let path = std::env::temp_dir().join(format!("timers-handoff-{}.json", std::process::id()));
+        let handoff = Handoff {
+            saved_at: SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            timers: vec![HandoffTimer {
+                summary: TimerSummary {
+                    label: Some("tea".to_string()),
+                    mode: "countdown".to_string(),
+                    value: Duration::from_millis(95_250),
+                    running: true,
+                    ..TimerSummary::default()
+                },
+                measuring: Mode::Countdown(Duration::from_secs(180)),
+                lead_in_left: Duration::ZERO,
+                settings: TimerSettings {
+                    tick_last: 3,
+                    ..TimerSettings::default()
+                },
+            }],
+            selected: 0,
+            paused_all: false,
+        };
+
+        handoff.save(&path).unwrap();
+        assert_eq!(take(&path).unwrap(), Some(handoff));
+        assert_eq!(take(&path).unwrap(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn a_pipe_left_behind_does_not_count_as_running() {
+        use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+        let path = std::env::temp_dir().join(format!("timers-stale-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let fifo = CString::new(path.as_os_str().as_bytes()).unwrap();
+        // SAFETY: `fifo` is a valid NUL terminated string that outlives the call
+        assert_eq!(unsafe { libc::mkfifo(fifo.as_ptr(), 0o600) }, 0);
+        assert!(!is_running(&path));
+        assert_eq!(
+            request_handoff(&path, &path.with_extension("json"))
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::NotFound
+        );
+
+        let (pipe, _commands) = control::ControlPipe::create(&path).unwrap();
+        let deadline = Instant::now() + HANDOFF_TIMEOUT;
+        while !is_running(&path) && Instant::now() < deadline {
+            thread::sleep(HANDOFF_POLL);
+        }
+        assert!(is_running(&path));
+        drop(pipe);
+        assert!(!path.exists());
+    }
+}
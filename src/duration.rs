@@ -0,0 +1,420 @@
+use crate::Precision;
+use std::time::Duration;
+
+/// How [`format_duration`] writes a duration
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FormatOptions {
+    pub precision: Precision,
+    /// Round to the nearest second instead of down, when the precision is whole seconds
+    pub round: bool,
+    /// Split whole days off the hours once there are any, as in `1d 02:03:04`
+    pub days: bool,
+}
+
+impl From<Precision> for FormatOptions {
+    fn from(precision: Precision) -> Self {
+        FormatOptions {
+            precision,
+            ..FormatOptions::default()
+        }
+    }
+}
+
+/// Format a duration as `HH:MM:SS` followed by as much of the fraction as the precision asks
+/// for, with hours going past 99 rather than wrapping unless days are split off
+pub fn format_duration(duration: Duration, options: &FormatOptions) -> String {
+    // Rounding happens before anything is split up, so 59.5s carries into the next minute
+    let duration = if options.round && options.precision == Precision::Seconds {
+        let round_up = duration.subsec_millis() >= 500;
+        Duration::from_secs(duration.as_secs() + u64::from(round_up))
+    } else {
+        duration
+    };
+    let total_hours = duration.as_secs() / 60 / 60;
+    let (days, hours) = if options.days && total_hours >= 24 {
+        (Some(total_hours / 24), total_hours % 24)
+    } else {
+        (None, total_hours)
+    };
+    let minutes = duration.as_secs() / 60 % 60;
+    let seconds = duration.as_secs() % 60;
+    let milliseconds = duration.subsec_millis();
+    let time = match options.precision {
+        Precision::Milliseconds => {
+            format!("{hours:02}:{minutes:02}:{seconds:02}.{milliseconds:03}")
+        }
+        Precision::Tenths => {
+            let tenths = milliseconds / 100;
+            format!("{hours:02}:{minutes:02}:{seconds:02}.{tenths}")
+        }
+        Precision::Seconds | Precision::Bar => format!("{hours:02}:{minutes:02}:{seconds:02}"),
+        Precision::Minutes => format!("{hours:02}:{minutes:02}"),
+    };
+    match days {
+        Some(days) => format!("{days}d {time}"),
+        None => time,
+    }
+}
+
+/// Parse a duration given on the command line
+///
+/// Accepts unit suffixed values such as `90s`, `5m` or `1h30m`, clock style values such as
+/// `1:30` or `01:00:00`, and bare numbers which are taken as seconds. Phrases such as
+/// `in 5 minutes` or `1 hour and 30 minutes` are accepted too, with an optional leading `in`
+/// and units spelled out.
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let original = input.trim();
+    let input = strip_in(original);
+    if input.is_empty() {
+        return Err("duration cannot be empty".to_string());
+    }
+
+    let seconds = if input.contains(':') {
+        parse_clock(input)
+    } else if let Ok(seconds) = input.parse::<f64>() {
+        Ok(seconds)
+    } else {
+        parse_units(input)
+    };
+    // Errors quote what was given, `in` and all, rather than what was left of it
+    seconds
+        .and_then(|seconds| {
+            Duration::try_from_secs_f64(seconds).map_err(|_| "negative or too long".to_string())
+        })
+        .map_err(|problem| format!("invalid duration `{original}`: {problem}"))
+}
+
+/// Drop the `in` from the front of a phrase like `in 5 minutes`
+fn strip_in(input: &str) -> &str {
+    match input.get(..3) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("in ") => input[3..].trim_start(),
+        _ => input,
+    }
+}
+
+/// The seconds in `MM:SS` or `HH:MM:SS`, where only the last field may have a fractional part
+/// and only the first may be 60 or more
+fn parse_clock(input: &str) -> Result<f64, String> {
+    let fields: Vec<&str> = input.split(':').collect();
+    if fields.len() > 3 {
+        return Err("too many `:` separated fields".to_string());
+    }
+
+    let mut seconds = 0.0;
+    for (index, field) in fields.iter().enumerate() {
+        let last = index == fields.len() - 1;
+        // Checked by hand, as parsing would also take signs, exponents and `inf`
+        let digits = field
+            .split_once('.')
+            .filter(|_| last)
+            .map_or([*field, "0"], |(whole, fraction)| [whole, fraction]);
+        if digits
+            .iter()
+            .any(|digits| digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()))
+        {
+            return Err(format!(
+                "`{field}` is not a whole number{}",
+                if last { " or decimal" } else { "" }
+            ));
+        }
+        let value: f64 = field
+            .parse()
+            .map_err(|_| format!("`{field}` is not a number"))?;
+        if index > 0 && value >= 60.0 {
+            return Err(format!("`{field}` is 60 or more"));
+        }
+        seconds = seconds * 60.0 + value;
+    }
+    Ok(seconds)
+}
+
+/// The seconds in a sequence of number and unit pairs such as `1h30m`, `2m 15s` or
+/// `1 hour and 30 minutes`
+fn parse_units(input: &str) -> Result<f64, String> {
+    let mut total = 0.0;
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let (number, after_number) = rest.split_at(number_end);
+        if number.is_empty() {
+            return Err("expected a number".to_string());
+        }
+        let value: f64 = number
+            .parse()
+            .map_err(|_| format!("`{number}` is not a number"))?;
+
+        let after_number = after_number.trim_start();
+        let unit_end = after_number
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(after_number.len());
+        let (unit, after_unit) = after_number.split_at(unit_end);
+        let multiplier = match unit.to_ascii_lowercase().as_str() {
+            "h" | "hr" | "hrs" | "hour" | "hours" => 3600.0,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60.0,
+            "s" | "sec" | "secs" | "second" | "seconds" => 1.0,
+            "ms" | "millisecond" | "milliseconds" => 0.001,
+            "" => return Err(format!("`{number}` has no unit")),
+            _ => return Err(format!("unknown unit `{unit}`")),
+        };
+
+        total += value * multiplier;
+        rest = skip_separator(after_unit.trim_start());
+    }
+
+    Ok(total)
+}
+
+/// Skip a `,` or `and` between the parts of a phrase like `1 hour, 5 minutes and 3 seconds`
+fn skip_separator(input: &str) -> &str {
+    if let Some(rest) = input.strip_prefix(',') {
+        return rest.trim_start();
+    }
+    match input.get(..4) {
+        Some(word) if word.eq_ignore_ascii_case("and ") => input[4..].trim_start(),
+        _ => input,
+    }
+}
+
+/// A template such as `%H:%M:%S` for printing a duration, checked when it is parsed
+///
+/// `%H`, `%M` and `%S` are the zero padded hours, minutes and seconds, `%L` the zero padded
+/// milliseconds, `%T` the total whole seconds, `%l` the total whole milliseconds, and `%%` a
+/// literal `%`. Hours are not wrapped at a day.
+#[derive(Clone, Debug)]
+pub struct TimeFormat {
+    template: String,
+}
+
+impl TimeFormat {
+    pub fn parse(template: &str) -> Result<TimeFormat, String> {
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                continue;
+            }
+            match chars.next() {
+                Some('H' | 'M' | 'S' | 'L' | 'T' | 'l' | '%') => {}
+                Some(other) => {
+                    return Err(format!(
+                        "unknown specifier `%{other}` in `{template}`, expected one of \
+                         %H, %M, %S, %L, %T, %l or %%"
+                    ))
+                }
+                None => return Err(format!("`{template}` ends with a lone `%`")),
+            }
+        }
+        Ok(TimeFormat {
+            template: template.to_string(),
+        })
+    }
+
+    /// The template as it was given
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// Fill the template in with `duration`
+    pub fn format(&self, duration: Duration) -> String {
+        let mut output = String::new();
+        let mut chars = self.template.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                output.push(c);
+                continue;
+            }
+            // Parsing has already checked every specifier
+            match chars.next() {
+                Some('H') => output.push_str(&format!("{:02}", duration.as_secs() / 3600)),
+                Some('M') => output.push_str(&format!("{:02}", duration.as_secs() / 60 % 60)),
+                Some('S') => output.push_str(&format!("{:02}", duration.as_secs() % 60)),
+                Some('L') => output.push_str(&format!("{:03}", duration.subsec_millis())),
+                Some('T') => output.push_str(&duration.as_secs().to_string()),
+                Some('l') => output.push_str(&duration.as_millis().to_string()),
+                _ => output.push('%'),
+            }
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `duration` formatted with `precision` and nothing else
+    fn plain(duration: Duration, precision: Precision) -> String {
+        format_duration(duration, &precision.into())
+    }
+
+    #[test]
+    fn durations_are_formatted_at_every_precision() {
+        let cases = [
+            (
+                Duration::ZERO,
+                ["00:00:00.000", "00:00:00.0", "00:00:00", "00:00"],
+            ),
+            (
+                Duration::from_secs(3600),
+                ["01:00:00.000", "01:00:00.0", "01:00:00", "01:00"],
+            ),
+            (
+                Duration::from_millis(59_999),
+                ["00:00:59.999", "00:00:59.9", "00:00:59", "00:00"],
+            ),
+            (
+                Duration::from_millis(3_599_999),
+                ["00:59:59.999", "00:59:59.9", "00:59:59", "00:59"],
+            ),
+            (
+                Duration::from_secs(345 * 3600 + 6 * 60 + 7),
+                ["345:06:07.000", "345:06:07.0", "345:06:07", "345:06"],
+            ),
+        ];
+        for (duration, [milliseconds, tenths, seconds, minutes]) in cases {
+            assert_eq!(plain(duration, Precision::Milliseconds), milliseconds);
+            assert_eq!(plain(duration, Precision::Tenths), tenths);
+            assert_eq!(plain(duration, Precision::Seconds), seconds);
+            assert_eq!(plain(duration, Precision::Bar), seconds);
+            assert_eq!(plain(duration, Precision::Minutes), minutes);
+        }
+    }
+
+    #[test]
+    fn rounding_carries_into_the_minutes_hours_and_days() {
+        let rounded = |millis, days| {
+            let options = FormatOptions {
+                precision: Precision::Seconds,
+                round: true,
+                days,
+            };
+            format_duration(Duration::from_millis(millis), &options)
+        };
+        assert_eq!(rounded(59_499, false), "00:00:59");
+        assert_eq!(rounded(59_500, false), "00:01:00");
+        assert_eq!(rounded(3_599_999, false), "01:00:00");
+        assert_eq!(rounded(86_399_500, true), "1d 00:00:00");
+
+        // Only whole seconds are rounded; anything finer already shows the fraction
+        let options = FormatOptions {
+            precision: Precision::Tenths,
+            round: true,
+            days: false,
+        };
+        assert_eq!(
+            format_duration(Duration::from_millis(59_999), &options),
+            "00:00:59.9"
+        );
+    }
+
+    #[test]
+    fn days_are_split_off_only_once_there_is_one() {
+        let with_days = |duration, precision| {
+            let options = FormatOptions {
+                precision,
+                round: false,
+                days: true,
+            };
+            format_duration(duration, &options)
+        };
+        assert_eq!(
+            with_days(Duration::from_millis(86_399_999), Precision::Milliseconds),
+            "23:59:59.999"
+        );
+        assert_eq!(
+            with_days(Duration::from_secs(86_400), Precision::Seconds),
+            "1d 00:00:00"
+        );
+        assert_eq!(
+            with_days(Duration::from_secs(345 * 3600 + 6 * 60), Precision::Minutes),
+            "14d 09:06"
+        );
+    }
+
+    #[test]
+    fn durations_parse_from_units_clocks_and_bare_seconds() {
+        let parsed = |input| parse_duration(input).unwrap();
+        assert_eq!(parsed("90s"), Duration::from_secs(90));
+        assert_eq!(parsed("1h30m"), Duration::from_secs(5400));
+        assert_eq!(parsed("2m 15s"), Duration::from_secs(135));
+        assert_eq!(parsed("250ms"), Duration::from_millis(250));
+        assert_eq!(parsed("1:30"), Duration::from_secs(90));
+        assert_eq!(parsed("01:00:00.5"), Duration::from_millis(3_600_500));
+        assert_eq!(parsed("5"), Duration::from_secs(5));
+        assert_eq!(parsed(" 1.5 "), Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn durations_parse_from_phrases() {
+        let parsed = |input| parse_duration(input).unwrap();
+        assert_eq!(parsed("in 5 minutes"), Duration::from_secs(300));
+        assert_eq!(parsed("IN 2m"), Duration::from_secs(120));
+        assert_eq!(parsed("1 hour and 30 minutes"), Duration::from_secs(5400));
+        assert_eq!(
+            parsed("1 hour, 5 minutes and 3 seconds"),
+            Duration::from_secs(3903)
+        );
+        assert_eq!(parsed("1 Hour AND 1 Sec"), Duration::from_secs(3601));
+    }
+
+    #[test]
+    fn invalid_durations_quote_what_was_given() {
+        let error = |input| parse_duration(input).unwrap_err();
+        assert_eq!(error(""), "duration cannot be empty");
+        assert_eq!(error("  "), "duration cannot be empty");
+        assert_eq!(
+            error("5 parsecs"),
+            "invalid duration `5 parsecs`: unknown unit `parsecs`"
+        );
+        assert_eq!(
+            error("and 5m"),
+            "invalid duration `and 5m`: expected a number"
+        );
+        assert_eq!(
+            error("in five minutes"),
+            "invalid duration `in five minutes`: expected a number"
+        );
+        assert_eq!(error("5m 3"), "invalid duration `5m 3`: `3` has no unit");
+        assert_eq!(
+            error("1:2:3:4"),
+            "invalid duration `1:2:3:4`: too many `:` separated fields"
+        );
+        assert_eq!(error("-5"), "invalid duration `-5`: negative or too long");
+        assert_eq!(
+            error("1:-5"),
+            "invalid duration `1:-5`: `-5` is not a whole number or decimal"
+        );
+        assert_eq!(error("1:99"), "invalid duration `1:99`: `99` is 60 or more");
+        assert_eq!(
+            error("1:60:00"),
+            "invalid duration `1:60:00`: `60` is 60 or more"
+        );
+        assert_eq!(
+            error("1.5:30"),
+            "invalid duration `1.5:30`: `1.5` is not a whole number"
+        );
+        assert!(parse_duration("1:").is_err());
+        assert!(parse_duration("1:30.").is_err());
+        assert!(parse_duration("1:+5").is_err());
+        assert_eq!(parse_duration("90:00"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("0:59.75"), Ok(Duration::from_millis(59_750)));
+        assert!(parse_duration("in").is_err());
+    }
+
+    #[test]
+    fn format_fills_in_every_specifier() {
+        let format = TimeFormat::parse("%H:%M:%S.%L %T %l 100%%").unwrap();
+        assert_eq!(
+            format.format(Duration::from_millis(3_723_045)),
+            "01:02:03.045 3723 3723045 100%"
+        );
+    }
+
+    #[test]
+    fn unknown_specifiers_are_rejected() {
+        assert!(TimeFormat::parse("%H:%Q").is_err());
+        assert!(TimeFormat::parse("%S%").is_err());
+    }
+}
@@ -0,0 +1,3219 @@
+//! A stopwatch and countdown timer widget for [ratatui]
+//!
+//! [`Timers`] is a [`StatefulWidget`] that draws the current time in big digits. It can be driven
+//! by [`Timers::run`] in a terminal set up with [`tui::init`], or embedded in another app by
+//! calling [`Timers::apply`] with [`Action`]s and [`Timers::tick`] from its own event loop.
+
+use awake::Inhibitor;
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+use completion::CompletionAction;
+use config::Sounds;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use daemon::{Handoff, HandoffTimer};
+use duration::{format_duration, FormatOptions};
+use pie::Pie;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Direction, Flex, Layout, Position, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Bar, BarChart, BarGroup, Block, StatefulWidget, Widget},
+    Frame,
+};
+use session::{Session, SessionSummary, TimerSummary};
+use std::{
+    f64::consts::TAU,
+    io::{stdout, Result, Write},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    sync::{mpsc::Receiver, Arc, Mutex},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+use timer::TimerList;
+use tui::Tui;
+use tui_widgets::big_text::{BigText, PixelSize};
+
+mod awake;
+pub mod code;
+pub mod completion;
+pub mod config;
+pub mod control;
+pub mod daemon;
+pub mod duration;
+mod pie;
+pub mod serve;
+pub mod session;
+mod timer;
+mod transform;
+pub mod tui;
+
+pub use timer::{Lap, Mode, Timer, TimerEvent, TimerSettings};
+
+/// Which edge of the block the instructions are drawn on; the label takes the other one
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum TitlePosition {
+    Top,
+    #[default]
+    Bottom,
+}
+
+/// The size of the big digits, mirroring [`PixelSize`] so it can be picked on the command line
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum DigitSize {
+    Full,
+    HalfHeight,
+    HalfWidth,
+    #[default]
+    Quadrant,
+    ThirdHeight,
+    Sextant,
+}
+
+impl DigitSize {
+    /// The number of font pixels drawn in a single cell, horizontally and vertically
+    fn pixels_per_cell(self) -> (u16, u16) {
+        match self {
+            DigitSize::Full => (1, 1),
+            DigitSize::HalfHeight => (1, 2),
+            DigitSize::HalfWidth => (2, 1),
+            DigitSize::Quadrant => (2, 2),
+            DigitSize::ThirdHeight => (1, 3),
+            DigitSize::Sextant => (2, 3),
+        }
+    }
+
+    /// The number of rows a single line of 8x8 glyphs takes up
+    fn height(self) -> u16 {
+        let (_, step_y) = self.pixels_per_cell();
+        8_u16.div_ceil(step_y)
+    }
+
+    /// The number of columns a single 8x8 glyph takes up
+    fn width(self) -> u16 {
+        let (step_x, _) = self.pixels_per_cell();
+        8_u16.div_ceil(step_x)
+    }
+
+    /// Every size, from the one taking up the most cells to the one taking up the fewest
+    const LARGEST_FIRST: [DigitSize; 6] = [
+        DigitSize::Full,
+        DigitSize::HalfWidth,
+        DigitSize::HalfHeight,
+        DigitSize::Quadrant,
+        DigitSize::ThirdHeight,
+        DigitSize::Sextant,
+    ];
+}
+
+impl From<DigitSize> for PixelSize {
+    fn from(size: DigitSize) -> Self {
+        match size {
+            DigitSize::Full => PixelSize::Full,
+            DigitSize::HalfHeight => PixelSize::HalfHeight,
+            DigitSize::HalfWidth => PixelSize::HalfWidth,
+            DigitSize::Quadrant => PixelSize::Quadrant,
+            DigitSize::ThirdHeight => PixelSize::ThirdHeight,
+            DigitSize::Sextant => PixelSize::Sextant,
+        }
+    }
+}
+
+/// How the display reacts when a countdown finishes
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum AlarmEffect {
+    /// Leave the display as it is
+    #[default]
+    None,
+    /// Blink the digits on and off
+    Blink,
+    /// Flash the whole background in the alarm color
+    Flash,
+}
+
+/// How far the big digits are turned, for a display read from the other side of a table
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Rotation {
+    #[default]
+    #[value(name = "0")]
+    None,
+    /// Upside down
+    #[value(name = "180")]
+    Half,
+}
+
+/// The big words shown in place of the digits as a countdown starts and finishes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Banners {
+    /// Flashed as a lead-in runs out and the timer itself starts
+    pub go: String,
+    /// Shown once a countdown finishes, until a key is pressed
+    pub done: String,
+    /// How long the go banner stays up
+    pub go_for: Duration,
+}
+
+impl Default for Banners {
+    fn default() -> Self {
+        Banners {
+            go: "GO!".to_string(),
+            done: "DONE".to_string(),
+            go_for: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Timed phases run one after another as a single countdown, such as rounds of a workout
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Workout {
+    /// One round of phases, in the order they are run
+    pub phases: Vec<Phase>,
+    /// How many times the round is run
+    pub repeat: u32,
+}
+
+impl Workout {
+    /// How many phases are run in all, counting every round
+    pub fn len(&self) -> usize {
+        self.phases.len().saturating_mul(self.repeat as usize)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The phase run `index`th, counting from zero across every round
+    pub fn phase(&self, index: usize) -> Option<&Phase> {
+        if index >= self.len() {
+            return None;
+        }
+        self.phases.get(index % self.phases.len())
+    }
+}
+
+/// A single stretch of a [`Workout`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Phase {
+    /// Shown as the timer's label while the phase runs
+    pub name: String,
+    pub duration: Duration,
+    /// The color of the digits during the phase, instead of the theme's
+    pub color: Option<Color>,
+    /// Played as the phase starts, after the one before it finishes
+    pub sound: Option<PathBuf>,
+}
+
+/// Where the digits sit across the block
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Align {
+    Left,
+    #[default]
+    Center,
+    Right,
+}
+
+/// The glyphs digits are written with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Numerals {
+    /// Plain digits, drawn big
+    #[default]
+    Ascii,
+    /// Full-width digits, two columns each, written as text since the big digit font has no
+    /// shapes for them
+    Fullwidth,
+}
+
+impl Numerals {
+    /// `text` with every ASCII digit swapped for this style's glyph
+    pub fn apply(self, text: &str) -> String {
+        match self {
+            Numerals::Ascii => text.to_string(),
+            Numerals::Fullwidth => text
+                .chars()
+                .map(|c| match c.to_digit(10) {
+                    Some(digit) => char::from_u32('０' as u32 + digit).unwrap_or(c),
+                    None => c,
+                })
+                .collect(),
+        }
+    }
+
+    /// Whether the big digit font can draw these numerals
+    fn has_big_digits(self) -> bool {
+        self == Numerals::Ascii
+    }
+}
+
+impl From<Align> for Alignment {
+    fn from(align: Align) -> Self {
+        match align {
+            Align::Left => Alignment::Left,
+            Align::Center => Alignment::Center,
+            Align::Right => Alignment::Right,
+        }
+    }
+}
+
+/// How long each on or off phase of the alarm effect lasts
+const ALARM_PHASE: Duration = Duration::from_millis(250);
+
+/// How many times the alarm effect flashes before the display settles
+const ALARM_FLASHES: u32 = 6;
+
+/// The fewest rows the progress pie is drawn in, below which it isn't recognisably round
+const PIE_MIN_ROWS: u16 = 3;
+
+/// The most rows the progress pie grows to
+const PIE_MAX_ROWS: u16 = 8;
+
+/// How often a timer running in the background rewrites its handoff file
+const HANDOFF_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The color of the digits while a lead-in is counted down, so it isn't mistaken for the timer
+const LEAD_IN_COLOR: Color = Color::Yellow;
+
+/// The color of the digits while a finished countdown counts how long it has run over
+const OVERRUN_COLOR: Color = Color::Red;
+
+/// How many times the background flashes when a countdown passes halfway
+const HALFWAY_FLASHES: u32 = 2;
+
+/// How long the note that a countdown is halfway through stays on screen, fading for the second
+/// half of it
+const HALFWAY_NOTE: Duration = Duration::from_secs(2);
+
+/// How long the note that a save slot was saved or loaded stays in the block title
+const SLOT_NOTE: Duration = Duration::from_secs(2);
+
+/// The save slots there are keys for, numbered from 1
+const SLOTS: RangeInclusive<u8> = 1..=9;
+
+#[derive(Clone, Copy)]
+pub struct Theme {
+    pub fg: Color,
+    pub bg: Color,
+    pub alarm: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            fg: Color::White,
+            bg: Color::Black,
+            alarm: Color::Red,
+        }
+    }
+}
+
+impl Theme {
+    /// The built in themes that can be picked by name
+    pub const PRESETS: [(&'static str, Theme); 8] = [
+        (
+            "default",
+            Theme::new(Color::White, Color::Black, Color::Red),
+        ),
+        ("light", Theme::new(Color::Black, Color::White, Color::Red)),
+        (
+            "matrix",
+            Theme::new(Color::Green, Color::Black, Color::LightGreen),
+        ),
+        (
+            "amber",
+            Theme::new(Color::Rgb(255, 176, 0), Color::Black, Color::Red),
+        ),
+        (
+            "solarized",
+            Theme::new(
+                Color::Rgb(147, 161, 161),
+                Color::Rgb(0, 43, 54),
+                Color::Rgb(220, 50, 47),
+            ),
+        ),
+        (
+            "gruvbox",
+            Theme::new(
+                Color::Rgb(235, 219, 178),
+                Color::Rgb(40, 40, 40),
+                Color::Rgb(251, 73, 52),
+            ),
+        ),
+        (
+            "dracula",
+            Theme::new(
+                Color::Rgb(248, 248, 242),
+                Color::Rgb(40, 42, 54),
+                Color::Rgb(255, 85, 85),
+            ),
+        ),
+        (
+            "nord",
+            Theme::new(
+                Color::Rgb(216, 222, 233),
+                Color::Rgb(46, 52, 64),
+                Color::Rgb(191, 97, 106),
+            ),
+        ),
+    ];
+
+    pub const fn new(fg: Color, bg: Color, alarm: Color) -> Self {
+        Theme { fg, bg, alarm }
+    }
+
+    /// Look up one of the [`Theme::PRESETS`] by name
+    pub fn preset(name: &str) -> Option<Theme> {
+        Theme::PRESETS
+            .iter()
+            .find(|(preset, _)| preset.eq_ignore_ascii_case(name))
+            .map(|&(_, theme)| theme)
+    }
+
+    /// Fill in whichever of `fg` and `bg` wasn't given with black or white, whichever stands out
+    /// more against the one that was; with both or neither given the theme's colors are used
+    pub fn with_auto_contrast(self, fg: Option<Color>, bg: Option<Color>) -> Theme {
+        let (fg, bg) = match (fg, bg) {
+            (Some(fg), None) => (fg, contrasting(fg).unwrap_or(self.bg)),
+            (None, Some(bg)) => (contrasting(bg).unwrap_or(self.fg), bg),
+            (fg, bg) => (fg.unwrap_or(self.fg), bg.unwrap_or(self.bg)),
+        };
+        Theme { fg, bg, ..self }
+    }
+
+    /// The same theme with the foreground and background swapped
+    pub fn inverted(self) -> Theme {
+        Theme {
+            fg: self.bg,
+            bg: self.fg,
+            ..self
+        }
+    }
+}
+
+/// Black or white, whichever has the better contrast ratio against `color`, or `None` for
+/// colors such as [`Color::Reset`] whose appearance depends on the terminal
+fn contrasting(color: Color) -> Option<Color> {
+    let luminance = relative_luminance(color)?;
+    // The point where the contrast ratio against black and against white is the same
+    Some(if luminance > 0.179 {
+        Color::Black
+    } else {
+        Color::White
+    })
+}
+
+/// The WCAG relative luminance of `color`, taking the named colors as xterm draws them
+fn relative_luminance(color: Color) -> Option<f64> {
+    let (r, g, b) = match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Indexed(index) => indexed_rgb(index),
+        Color::Reset => return None,
+        named => indexed_rgb(ansi_index(named)?),
+    };
+    let linear = |channel: u8| {
+        let channel = f64::from(channel) / 255.0;
+        if channel <= 0.04045 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    Some(0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b))
+}
+
+/// The position of a named color in the 16 color ANSI palette
+fn ansi_index(color: Color) -> Option<u8> {
+    let index = match color {
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        _ => return None,
+    };
+    Some(index)
+}
+
+/// The RGB value of an entry in the 256 color palette
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    const ANSI: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (0, 0, 238),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+    match index {
+        0..=15 => ANSI[usize::from(index)],
+        16..=231 => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let cube = index - 16;
+            (level(cube / 36), level(cube / 6 % 6), level(cube % 6))
+        }
+        232..=255 => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+/// How much of the sub-second part of the time is shown
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Precision {
+    /// `HH:MM:SS.mmm`
+    #[default]
+    #[value(name = "ms")]
+    Milliseconds,
+    /// `HH:MM:SS.t`
+    Tenths,
+    /// `HH:MM:SS`
+    #[value(name = "none")]
+    Seconds,
+    /// `HH:MM:SS` with a bar under the seconds that fills up once a second
+    Bar,
+    /// `HH:MM`, dropping the seconds as well, picked with `--hm` rather than by name
+    #[value(skip)]
+    Minutes,
+}
+
+/// Something that can be done to a [`Timers`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Start or stop the timer
+    Toggle,
+    /// Put the timer back to its starting value
+    Reset,
+    /// Record the current time as a lap
+    Lap,
+    /// Move the selection to the next of several stacked timers
+    SelectNext,
+    /// Move the selection to the previous of several stacked timers
+    SelectPrevious,
+    /// Hold or release every timer regardless of its own running state
+    PauseAll,
+    /// Swap the foreground and background colors
+    Invert,
+    /// Turn the selected stopwatch into a countdown or back again
+    SwitchMode,
+    /// Put the selected timer at this much elapsed, or remaining for a countdown
+    Set(Duration),
+    /// Shrink the display to a single line, or grow it back to the big digits
+    Collapse,
+    /// Turn the go and done banners on or off
+    Banners,
+    /// Stop running the app, leaving the timers running in the background
+    Detach,
+    /// Wait for a digit key, then save every timer to the slot it numbers
+    ChooseSaveSlot,
+    /// Save every timer to this numbered slot
+    SaveSlot(u8),
+    /// Replace every timer with the ones saved to this numbered slot
+    LoadSlot(u8),
+    /// Stop running the app
+    Quit,
+}
+
+impl Action {
+    /// The action bound to a key press in the TUI, if any
+    pub fn from_key(key_event: KeyEvent) -> Option<Action> {
+        if key_event.kind != KeyEventKind::Press {
+            return None;
+        }
+        match (key_event.code, key_event.modifiers) {
+            (KeyCode::Esc, KeyModifiers::NONE) => Some(Action::Quit),
+            // Raw mode turns Ctrl+C into a key press instead of a signal
+            (KeyCode::Char('c'), KeyModifiers::CONTROL) => Some(Action::Quit),
+            (KeyCode::Char(' '), KeyModifiers::NONE) => Some(Action::Toggle),
+            (KeyCode::Char('r'), KeyModifiers::NONE) => Some(Action::Reset),
+            (KeyCode::Char('l'), KeyModifiers::NONE) => Some(Action::Lap),
+            (KeyCode::Char('p'), KeyModifiers::NONE) => Some(Action::PauseAll),
+            (KeyCode::Char('i'), KeyModifiers::NONE) => Some(Action::Invert),
+            (KeyCode::Char('m'), KeyModifiers::NONE) => Some(Action::SwitchMode),
+            (KeyCode::Char('d'), KeyModifiers::NONE) => Some(Action::Detach),
+            (KeyCode::Char('c'), KeyModifiers::NONE) => Some(Action::Collapse),
+            (KeyCode::Char('b'), KeyModifiers::NONE) => Some(Action::Banners),
+            // Ctrl+digit never reaches the app on most terminals, so saving takes two keys
+            (KeyCode::Char('s'), KeyModifiers::NONE) => Some(Action::ChooseSaveSlot),
+            (KeyCode::Char(digit @ '1'..='9'), KeyModifiers::ALT) => {
+                Some(Action::LoadSlot(slot_number(digit)))
+            }
+            (KeyCode::Down | KeyCode::Tab, KeyModifiers::NONE) => Some(Action::SelectNext),
+            (KeyCode::Up, KeyModifiers::NONE) | (KeyCode::BackTab, _) => {
+                Some(Action::SelectPrevious)
+            }
+            _ => None,
+        }
+    }
+
+    /// Every key [`Action::from_key`] recognises and what it does, for showing to the user
+    pub const KEY_BINDINGS: [(&'static str, &'static str); 17] = [
+        ("Space", "toggle"),
+        ("R", "reset"),
+        ("L", "lap"),
+        ("P", "pause-all"),
+        ("I", "invert"),
+        ("M", "mode"),
+        ("D", "detach"),
+        ("C", "collapse"),
+        ("B", "banners"),
+        ("S, 1..9", "save-slot"),
+        ("Alt+1..9", "load-slot"),
+        ("Down", "next"),
+        ("Tab", "next"),
+        ("Up", "previous"),
+        ("Shift+Tab", "previous"),
+        ("Escape", "quit"),
+        ("Ctrl+C", "quit"),
+    ];
+}
+
+/// The save slot a digit key stands for
+fn slot_number(digit: char) -> u8 {
+    digit.to_digit(10).map_or(0, |digit| digit as u8)
+}
+
+/// The digit size of the elapsed time drawn under a countdown by the watermark
+const WATERMARK_SIZE: DigitSize = DigitSize::Sextant;
+
+/// How many of the most recent laps are listed under the digits
+const LAPS_SHOWN: usize = 5;
+
+/// Frames of the activity spinner drawn in the corner of the block
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// How many ticks each spinner frame is shown for
+const TICKS_PER_SPINNER_FRAME: usize = 6;
+
+pub struct TimersState {
+    pub theme: Theme,
+}
+
+#[derive(Default)]
+pub struct Timers {
+    exit: bool,
+    /// Whether the exit was asked for with [`Action::Detach`]
+    detached: bool,
+    /// Show a single line instead of the big digits
+    collapsed: bool,
+    timers: TimerList,
+    /// Holds every timer regardless of its own running state
+    paused_all: bool,
+    ticks: usize,
+    theme: Theme,
+    /// Whether the theme's foreground and background are currently swapped
+    inverted: bool,
+    title_position: TitlePosition,
+    digit_size: DigitSize,
+    align: Align,
+    /// Pad the time to the widest it is expected to get instead of recentering it
+    fixed_width: bool,
+    numerals: Numerals,
+    /// Text modifiers such as bold or italic applied to the big digits
+    digit_modifier: Modifier,
+    precision: Precision,
+    /// Round to the nearest second instead of down when the fraction isn't shown
+    round: bool,
+    /// Split whole days off the hours, as in `1d 02:03:04`
+    days: bool,
+    rotation: Rotation,
+    /// Draw the big digits left to right reversed, as if seen in a mirror
+    mirror: bool,
+    /// Emphasise the hours and minutes, dimming the seconds and dropping the fraction
+    focus: bool,
+    /// Draw a bar under a clock that sweeps across once a minute, like a seconds hand
+    sweep: bool,
+    /// Show how much of a countdown has elapsed, dimly and in small digits, under what remains
+    watermark: bool,
+    /// Show how far through a countdown, or towards a stopwatch's target, it is in the title
+    percent: bool,
+    /// Show how long the timer has spent paused in the title
+    show_paused: bool,
+    /// Draw a bar chart of the recent lap times beside the lap list
+    lap_chart: bool,
+    /// Planned time measured at each lap, oldest first, compared with the recorded laps
+    splits: Vec<Duration>,
+    /// Wall clock times the selected countdown is counting down to, soonest first; the first is
+    /// the one it is counting down to now
+    alarms: Vec<SystemTime>,
+    /// The workout the selected countdown runs through, one phase after another
+    workout: Workout,
+    /// Index into the workout of the phase running
+    phase: usize,
+    /// Whether the sound of the phase running has been played, which for the first waits until
+    /// the countdown is started
+    phase_announced: bool,
+    /// Draw a dot beside the seconds that breathes once a second while the timer runs
+    pulse: bool,
+    /// Show the banners in place of the digits as a countdown starts and finishes
+    banners_on: bool,
+    banners: Banners,
+    /// The banner being shown, the time it went up and how long it stays up for, or `None` to
+    /// stay up until a key is pressed
+    banner: Option<(String, Instant, Option<Duration>)>,
+    /// Draw a pie in the corner filling up with the countdown's or target's progress
+    pie: bool,
+    /// Flash and show a note when a countdown passes halfway
+    halfway: bool,
+    /// Also ring the bell when a countdown passes halfway
+    halfway_bell: bool,
+    /// When a countdown last passed halfway
+    halfway_at: Option<Instant>,
+    /// The directory save slots are kept in, without which saving and loading them does nothing
+    slots: Option<PathBuf>,
+    /// What happened with a save slot last, and when, shown briefly in the block title
+    slot_note: Option<(String, Instant)>,
+    /// Whether the next key is a digit picking the slot to save to
+    choosing_slot: bool,
+    /// Keep the terminal window's title showing the selected timer
+    set_title: bool,
+    /// The window title last set, so it is only sent again when it changes
+    window_title: Option<String>,
+    frame_rate: Option<Duration>,
+    /// When the timers were last brought up to date, so drawing can account for the time since
+    last_tick: Option<Instant>,
+    alarm_effect: AlarmEffect,
+    exit_on_finish: bool,
+    /// How long to keep alarming after the last countdown finishes before exiting on finish
+    grace: Duration,
+    /// When exiting on finish will happen, unless a key is pressed before then
+    exit_at: Option<Instant>,
+    /// How many times the bell has rung since the grace period started
+    grace_bells: u64,
+    completion_actions: Vec<CompletionAction>,
+    /// Which completion actions failed last time, shown on screen instead of failing
+    completion_error: Option<String>,
+    keep_awake: bool,
+    inhibitor: Option<Inhibitor>,
+    /// Why sleep could not be inhibited, shown on screen instead of failing
+    keep_awake_error: Option<String>,
+    /// Shared with a [`serve::StatusServer`], kept up to date after every tick
+    status: Option<Arc<Mutex<SessionSummary>>>,
+    /// Actions sent from outside the terminal, such as by a [`control::ControlPipe`]
+    commands: Option<Receiver<Action>>,
+    sounds: Sounds,
+    /// Exit after this long with every timer stopped and no input
+    idle_exit: Option<Duration>,
+    /// When there was last any input or a timer running
+    active_at: Option<Instant>,
+}
+
+impl Timers {
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Show a single timer
+    pub fn with_timer(self, timer: Timer) -> Self {
+        self.with_timers(vec![timer])
+    }
+
+    /// Show the timers stacked on top of each other, or the default stopwatch if there are none
+    pub fn with_timers(mut self, timers: Vec<Timer>) -> Self {
+        self.timers.replace(timers);
+        self
+    }
+
+    pub fn with_title_position(mut self, title_position: TitlePosition) -> Self {
+        self.title_position = title_position;
+        self
+    }
+
+    pub fn with_digit_size(mut self, digit_size: DigitSize) -> Self {
+        self.digit_size = digit_size;
+        self
+    }
+
+    /// Start showing a single line with each timer's time and state instead of the big digits,
+    /// until [`Action::Collapse`] grows it back
+    pub fn with_collapsed(mut self, collapsed: bool) -> Self {
+        self.collapsed = collapsed;
+        self
+    }
+
+    /// Write the time with these numerals, as text instead of big digits if the font has none
+    /// for them
+    pub fn with_numerals(mut self, numerals: Numerals) -> Self {
+        self.numerals = numerals;
+        self
+    }
+
+    /// Keep the save slots for [`Action::SaveSlot`] and [`Action::LoadSlot`] in this directory
+    pub fn with_slots(mut self, dir: PathBuf) -> Self {
+        self.slots = Some(dir);
+        self
+    }
+
+    /// Line the digits, and what goes under them, up with the left or right edge instead of
+    /// centering them
+    pub fn with_align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn with_digit_modifier(mut self, digit_modifier: Modifier) -> Self {
+        self.digit_modifier = digit_modifier;
+        self
+    }
+
+    pub fn with_precision(mut self, precision: Precision) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    pub fn with_round(mut self, round: bool) -> Self {
+        self.round = round;
+        self
+    }
+
+    /// Write times of a day or more with the days split off the hours, as in `1d 02:03:04`
+    pub fn with_days(mut self, days: bool) -> Self {
+        self.days = days;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_mirror(mut self, mirror: bool) -> Self {
+        self.mirror = mirror;
+        self
+    }
+
+    /// Turn the big digits already drawn in `area` by the rotation and mirroring asked for
+    fn orient_digits(&self, area: Rect, buf: &mut Buffer) {
+        let rotated = self.rotation == Rotation::Half;
+        // Mirroring something upside down flips it back left to right
+        transform::flip(buf, area, rotated != self.mirror, rotated);
+    }
+
+    pub fn with_focus(mut self, focus: bool) -> Self {
+        self.focus = focus;
+        self
+    }
+
+    pub fn with_sweep(mut self, sweep: bool) -> Self {
+        self.sweep = sweep;
+        self
+    }
+
+    pub fn with_watermark(mut self, watermark: bool) -> Self {
+        self.watermark = watermark;
+        self
+    }
+
+    pub fn with_percent(mut self, percent: bool) -> Self {
+        self.percent = percent;
+        self
+    }
+
+    pub fn with_show_paused(mut self, show_paused: bool) -> Self {
+        self.show_paused = show_paused;
+        self
+    }
+
+    /// Show the selected timer's time and state in the terminal window's title, putting the
+    /// original title back when the terminal is restored
+    pub fn with_set_title(mut self, set_title: bool) -> Self {
+        self.set_title = set_title;
+        self
+    }
+
+    /// Update the window title if the time it shows has changed
+    fn update_window_title(&mut self) -> Result<()> {
+        if !self.set_title {
+            return Ok(());
+        }
+        let timer = self.selected();
+        let state = if timer.is_finished() {
+            "finished"
+        } else if timer.is_running() && !self.paused_all {
+            "running"
+        } else {
+            "paused"
+        };
+        let time = format_duration(self.display_value(timer), &Precision::Seconds.into());
+        let title = format!("⏱ {time} ({state})");
+        if self.window_title.as_ref() != Some(&title) {
+            tui::set_title(&title)?;
+            self.window_title = Some(title);
+        }
+        Ok(())
+    }
+
+    /// Compare each recorded lap with the planned time measured by then, showing how far ahead
+    /// or behind it was
+    pub fn with_splits(mut self, splits: Vec<Duration>) -> Self {
+        self.splits = splits;
+        self
+    }
+
+    /// Count down to each of `alarms` in turn, the countdown already being set to the soonest;
+    /// when it finishes the next one is counted down to
+    pub fn with_alarms(mut self, mut alarms: Vec<SystemTime>) -> Self {
+        alarms.sort();
+        alarms.dedup();
+        self.alarms = alarms;
+        self
+    }
+
+    /// Run through the phases of `workout` in order as the selected countdown, which is already
+    /// set to the first of them
+    pub fn with_workout(mut self, workout: Workout) -> Self {
+        self.workout = workout;
+        self.phase = 0;
+        self.phase_announced = false;
+        self
+    }
+
+    /// The workout phase running, if there is a workout
+    fn current_phase(&self) -> Option<&Phase> {
+        self.workout.phase(self.phase)
+    }
+
+    /// Play the sound of the phase running, once it is running, unless it has been played
+    fn announce_phase(&mut self) {
+        if self.phase_announced || !self.selected().is_running() {
+            return;
+        }
+        if let Some(phase) = self.current_phase() {
+            Timers::play(&phase.sound);
+            self.phase_announced = true;
+        }
+    }
+
+    /// Move on to the next phase of the workout once one finishes, playing its sound, returning
+    /// whether there was one to move on to
+    fn next_phase(&mut self) -> bool {
+        let Some(next) = self.workout.phase(self.phase + 1).cloned() else {
+            return false;
+        };
+        self.phase += 1;
+        let timer = self.timers.selected_mut();
+        timer.restart_countdown(next.duration);
+        timer.set_label(Some(next.name));
+        self.phase_announced = false;
+        self.announce_phase();
+        true
+    }
+
+    /// Go back to the start of the first phase of the workout, if there is one, leaving whether
+    /// the countdown is running alone
+    fn reset_workout(&mut self) {
+        let Some(first) = self.workout.phase(0).cloned() else {
+            return;
+        };
+        self.phase = 0;
+        self.phase_announced = false;
+        let timer = self.timers.selected_mut();
+        timer.set_countdown(first.duration);
+        timer.set_label(Some(first.name));
+    }
+
+    /// Move on to counting down to the next alarm, if there is one after the one just finished
+    fn next_alarm(&mut self) {
+        if self.alarms.is_empty() {
+            return;
+        }
+        self.alarms.remove(0);
+        let now = SystemTime::now();
+        // Skip any that were passed while the last one was going off
+        self.alarms.retain(|&alarm| alarm > now);
+        if let Some(next) = self.alarms.first() {
+            let until = next.duration_since(now).unwrap_or(Duration::ZERO);
+            self.timers.selected_mut().restart_countdown(until);
+        }
+    }
+
+    /// Pad the time to the widest it is expected to get, so narrower values don't move the
+    /// digits about
+    pub fn with_fixed_width(mut self, fixed_width: bool) -> Self {
+        self.fixed_width = fixed_width;
+        self
+    }
+
+    /// Show big banners in place of the digits as a lead-in runs out and as a countdown
+    /// finishes, which [`Action::Banners`] turns off and on again
+    pub fn with_banners(mut self, banners: Banners) -> Self {
+        self.banners = banners;
+        self.banners_on = true;
+        self
+    }
+
+    /// Draw a dot after the digits that brightens and fades once a second, a sign the timer is
+    /// running without the flicker of sub-second digits, standing still while it is paused
+    pub fn with_pulse(mut self, pulse: bool) -> Self {
+        self.pulse = pulse;
+        self
+    }
+
+    /// Draw a pie in the top right corner that fills up as a countdown runs, or as a stopwatch
+    /// nears its target, when there is room above the digits for it
+    pub fn with_pie(mut self, pie: bool) -> Self {
+        self.pie = pie;
+        self
+    }
+
+    /// Draw the progress pie into the space between the top of `area` and the content
+    fn render_pie(&self, timer: &Timer, area: Rect, content_top: u16, buf: &mut Buffer) {
+        let Some(progress) = timer.progress(self.display_value(timer)) else {
+            return;
+        };
+        let rows = content_top.saturating_sub(area.y).min(PIE_MAX_ROWS);
+        let columns = Pie::width(rows);
+        if rows < PIE_MIN_ROWS || columns > area.width {
+            return;
+        }
+        let pie_area = Rect::new(area.right() - columns, area.y, columns, rows);
+        Pie::new(progress)
+            .style(Style::new().fg(self.theme.fg))
+            .render(pie_area, buf);
+    }
+
+    /// Flash the background and briefly note it on screen when a countdown passes halfway
+    pub fn with_halfway(mut self, halfway: bool) -> Self {
+        self.halfway = halfway;
+        self
+    }
+
+    /// Ring the bell as well when a countdown passes halfway; only has an effect alongside
+    /// [`Timers::with_halfway`]
+    pub fn with_halfway_bell(mut self, halfway_bell: bool) -> Self {
+        self.halfway_bell = halfway_bell;
+        self
+    }
+
+    /// Draw a bar chart of the recent lap times beside the lap list, each bar as long as its lap
+    /// took compared with the longest
+    pub fn with_lap_chart(mut self, lap_chart: bool) -> Self {
+        self.lap_chart = lap_chart;
+        self
+    }
+
+    /// How many times a second [`Timers::run`] redraws and updates the timer
+    pub fn with_fps(mut self, fps: f64) -> Self {
+        self.frame_rate = Some(Duration::from_secs_f64(1.0 / fps));
+        self
+    }
+
+    pub fn with_alarm_effect(mut self, alarm_effect: AlarmEffect) -> Self {
+        self.alarm_effect = alarm_effect;
+        self
+    }
+
+    /// Pick up the first timer from a previously saved [`Session`], if it is a stopwatch, along
+    /// with whether the theme was inverted
+    pub fn with_session(mut self, session: Session) -> Self {
+        let first = &mut self.timers.all_mut()[0];
+        *first = std::mem::take(first).with_progress(session.timer, session.laps);
+        if session.inverted != self.inverted {
+            self.apply(Action::Invert);
+        }
+        self
+    }
+
+    /// The parts of the first timer that are worth saving between launches
+    pub fn session(&self) -> Session {
+        let first = &self.timers.all()[0];
+        Session {
+            timer: first.value(),
+            laps: first.laps().to_vec(),
+            inverted: self.inverted,
+        }
+    }
+
+    pub fn with_exit_on_finish(mut self, exit_on_finish: bool) -> Self {
+        self.exit_on_finish = exit_on_finish;
+        self
+    }
+
+    /// Keep alarming for this long after the last countdown finishes before exiting on finish,
+    /// staying open if a key is pressed in the meantime
+    pub fn with_grace(mut self, grace: Duration) -> Self {
+        self.grace = grace;
+        self
+    }
+
+    /// Add an action to carry out, after any added before it, when a countdown finishes
+    pub fn with_completion_action(mut self, action: CompletionAction) -> Self {
+        self.completion_actions.push(action);
+        self
+    }
+
+    /// Play these sounds as the selected timer is started, paused, lapped or finishes
+    pub fn with_sounds(mut self, sounds: Sounds) -> Self {
+        self.sounds = sounds;
+        self
+    }
+
+    /// Play a sound if one has been set and the file is there, without waiting for it
+    fn play(sound: &Option<PathBuf>) {
+        if let Some(path) = sound.as_ref().filter(|path| path.exists()) {
+            let _ = CompletionAction::Sound(path.clone()).run();
+        }
+    }
+
+    pub fn with_keep_awake(mut self, keep_awake: bool) -> Self {
+        self.keep_awake = keep_awake;
+        self
+    }
+
+    /// Keep `status` up to date with [`Timers::summary`] while running
+    pub fn with_status(mut self, status: Arc<Mutex<SessionSummary>>) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Apply actions received on `commands` as well as those from key presses
+    pub fn with_commands(mut self, commands: Receiver<Action>) -> Self {
+        self.commands = Some(commands);
+        self
+    }
+
+    /// Apply every action that has arrived on the command channel since it was last checked
+    fn apply_commands(&mut self) {
+        let Some(commands) = &self.commands else {
+            return;
+        };
+        let actions: Vec<Action> = commands.try_iter().collect();
+        if actions.is_empty() {
+            return;
+        }
+        self.mark_active();
+        self.catch_up();
+        for action in actions {
+            self.apply(action);
+        }
+    }
+
+    /// A snapshot of every timer
+    pub fn summary(&self) -> SessionSummary {
+        let timers = self
+            .timers
+            .all()
+            .iter()
+            .map(Timers::timer_summary)
+            .collect();
+        SessionSummary {
+            timers,
+            selected: self.timers.selected_index(),
+            paused_all: self.paused_all,
+        }
+    }
+
+    fn timer_summary(timer: &Timer) -> TimerSummary {
+        TimerSummary {
+            label: timer.label().map(str::to_string),
+            mode: timer.mode().name().to_string(),
+            value: timer.value(),
+            running: timer.is_running(),
+            finished: timer.is_finished(),
+            paused: timer.paused_for(),
+            laps: timer.laps().to_vec(),
+            countdown: timer.overrun().map(|(countdown, _)| countdown),
+            overrun: timer.overrun().map(|(_, overrun)| overrun),
+        }
+    }
+
+    /// A line giving every countdown that was run over with `--then-stopwatch`, and by how much
+    pub fn overrun_summary(&self) -> Option<String> {
+        let overruns: Vec<String> = self
+            .timers
+            .all()
+            .iter()
+            .filter_map(|timer| {
+                let (countdown, overrun) = timer.overrun()?;
+                let name = timer.label().unwrap_or("countdown");
+                let countdown = format_duration(countdown, &Precision::Seconds.into());
+                let overrun = format_duration(overrun, &self.format_options());
+                Some(format!("{name} {countdown}, over by {overrun}"))
+            })
+            .collect();
+        (!overruns.is_empty()).then(|| overruns.join("; "))
+    }
+
+    /// Every recorded lap of every timer as CSV, always to the millisecond whatever precision
+    /// is shown on screen
+    pub fn laps_csv(&self) -> String {
+        let mut csv = String::from("timer,lap,kind,split,lap_time\n");
+        for timer in self.timers.all() {
+            let name = csv_field(timer.label().unwrap_or(timer.mode().name()));
+            for (index, lap) in timer.laps().iter().enumerate() {
+                let kind = if lap.auto { "auto" } else { "manual" };
+                let split = format_duration(lap.value, &Precision::Milliseconds.into());
+                let lap_time =
+                    format_duration(timer.lap_time(index), &Precision::Milliseconds.into());
+                csv.push_str(&format!("{name},{},{kind},{split},{lap_time}\n", index + 1));
+            }
+        }
+        csv
+    }
+
+    /// Everything needed for another process to carry on with the timers from now
+    pub fn handoff(&self) -> Handoff {
+        let timers = self
+            .timers
+            .all()
+            .iter()
+            .map(|timer| HandoffTimer {
+                summary: Timers::timer_summary(timer),
+                measuring: timer
+                    .overrun()
+                    .map_or(timer.mode(), |(countdown, _)| Mode::Countdown(countdown)),
+                lead_in_left: timer.lead_in_after(Duration::ZERO).unwrap_or_default(),
+                settings: timer.settings(),
+            })
+            .collect();
+        Handoff {
+            saved_at: SystemTime::now(),
+            timers,
+            selected: self.timers.selected_index(),
+            paused_all: self.paused_all,
+        }
+    }
+
+    /// Carry on with timers handed over by another process, catching up on the time since
+    pub fn with_handoff(mut self, handoff: Handoff) -> Self {
+        self.take_over(handoff);
+        self
+    }
+
+    /// Replace every timer with the ones in `handoff`, set up just as they were and caught up on
+    /// the time since it was saved
+    fn take_over(&mut self, handoff: Handoff) {
+        let age = handoff.age();
+        let timers = handoff
+            .timers
+            .into_iter()
+            .map(|timer| {
+                Timer::new(timer.measuring)
+                    .with_label(timer.summary.label.clone())
+                    .with_settings(timer.settings)
+                    .with_running(timer.summary.running)
+                    .with_handed_over(timer.summary, timer.lead_in_left)
+            })
+            .collect();
+        self.timers.replace(timers);
+        self.timers.select(handoff.selected);
+        self.paused_all = handoff.paused_all;
+        self.tick(age);
+    }
+
+    /// Where save slot `slot` is kept, if there is anywhere to keep it
+    fn slot_path(&self, slot: u8) -> Option<PathBuf> {
+        Some(self.slots.as_ref()?.join(format!("{slot}.json")))
+    }
+
+    /// Save every timer to a numbered slot, noting how it went in the block title
+    fn save_slot(&mut self, slot: u8) {
+        let Some(path) = self.slot_path(slot).filter(|_| SLOTS.contains(&slot)) else {
+            return;
+        };
+        let note = match self.handoff().save(&path) {
+            Ok(()) => format!("saved slot {slot}"),
+            Err(error) => format!("slot {slot}: {error}"),
+        };
+        self.slot_note = Some((note, Instant::now()));
+    }
+
+    /// Replace every timer with the ones saved to a numbered slot, noting how it went in the
+    /// block title
+    fn load_slot(&mut self, slot: u8) {
+        let Some(path) = self.slot_path(slot).filter(|_| SLOTS.contains(&slot)) else {
+            return;
+        };
+        let note = match Handoff::load(&path) {
+            Ok(Some(handoff)) => {
+                self.take_over(handoff);
+                format!("loaded slot {slot}")
+            }
+            Ok(None) => format!("slot {slot} is empty"),
+            Err(error) => format!("slot {slot}: {error}"),
+        };
+        self.slot_note = Some((note, Instant::now()));
+    }
+
+    /// The note about the last save slot saved or loaded, while it is still showing
+    fn slot_note(&self) -> Option<&str> {
+        self.slot_note
+            .as_ref()
+            .filter(|(_, at)| at.elapsed() < SLOT_NOTE)
+            .map(|(note, _)| note.as_str())
+    }
+
+    /// Copy the latest summary into the shared status, if there is one
+    fn publish_status(&self) {
+        if let Some(status) = &self.status {
+            if let Ok(mut status) = status.lock() {
+                *status = self.summary();
+            }
+        }
+    }
+
+    /// Exit once every timer has been stopped with no input for `timeout`
+    pub fn with_idle_exit(mut self, timeout: Option<Duration>) -> Self {
+        self.idle_exit = timeout;
+        self
+    }
+
+    /// Note that something happened, putting off an idle exit
+    fn mark_active(&mut self) {
+        self.active_at = Some(Instant::now());
+    }
+
+    /// Exit if nothing is moving and there has been no input for the idle timeout
+    fn check_idle(&mut self) {
+        let moving = !self.paused_all && self.timers.all().iter().any(Timer::is_advancing);
+        if moving {
+            self.mark_active();
+            return;
+        }
+        if let (Some(timeout), Some(active_at)) = (self.idle_exit, self.active_at) {
+            if active_at.elapsed() >= timeout {
+                self.exit = true;
+            }
+        }
+    }
+
+    /// Exit once the grace period after finishing is over, ringing the bell every second until
+    /// then if it was rung on finishing
+    fn check_grace(&mut self) {
+        let Some(exit_at) = self.exit_at else {
+            return;
+        };
+        let now = Instant::now();
+        if now >= exit_at {
+            self.exit = true;
+            return;
+        }
+        let alarming_for = self.grace.saturating_sub(exit_at - now).as_secs();
+        if alarming_for > self.grace_bells {
+            self.grace_bells = alarming_for;
+            if self.completion_actions.contains(&CompletionAction::Bell) {
+                let _ = CompletionAction::Bell.run();
+            }
+        }
+    }
+
+    /// Call off exiting at the end of the grace period, returning whether there was one to call off
+    fn acknowledge_grace(&mut self) -> bool {
+        self.exit_at.take().is_some()
+    }
+
+    /// How long is left of the grace period before exiting, if one is running
+    fn grace_left(&self) -> Option<Duration> {
+        Some(self.exit_at?.saturating_duration_since(Instant::now()))
+    }
+
+    pub fn run(&mut self, terminal: &mut Tui) -> Result<()> {
+        self.last_tick = Some(Instant::now());
+        self.mark_active();
+        while !self.exit {
+            terminal.draw(|frame| self.render_frame(frame))?;
+            self.update_window_title()?;
+            self.handle_events()?;
+            self.catch_up();
+            self.check_idle();
+            self.check_grace();
+            self.update_inhibitor();
+            self.publish_status();
+        }
+        self.inhibitor = None;
+        Ok(())
+    }
+
+    /// Run the timers without drawing them, taking commands from [`Timers::with_commands`] and
+    /// keeping a [`Handoff`] at `state` up to date until told to quit
+    pub fn run_headless(&mut self, state: &Path) -> Result<()> {
+        let frame_rate = self
+            .frame_rate
+            .unwrap_or(Duration::from_secs_f64(1.0 / 10.0));
+        let mut saved_at: Option<Instant> = None;
+        self.last_tick = Some(Instant::now());
+        while !self.exit {
+            thread::sleep(frame_rate);
+            self.apply_commands();
+            self.catch_up();
+            self.check_grace();
+            self.publish_status();
+            if saved_at.is_none_or(|saved_at| saved_at.elapsed() >= HANDOFF_INTERVAL) {
+                self.handoff().save(state)?;
+                saved_at = Some(Instant::now());
+            }
+        }
+        self.catch_up();
+        self.handoff().save(state)
+    }
+
+    /// Advance the timers by the real time since they were last brought up to date
+    fn catch_up(&mut self) {
+        let now = Instant::now();
+        let last_tick = self.last_tick.replace(now).unwrap_or(now);
+        self.tick(now - last_tick);
+    }
+
+    /// The value to draw for `timer`, including the time that has passed since the last tick
+    fn display_value(&self, timer: &Timer) -> Duration {
+        match self.last_tick {
+            Some(last_tick) if !self.paused_all => timer.value_after(last_tick.elapsed()),
+            _ => timer.value(),
+        }
+    }
+
+    /// Whether any timer is currently moving
+    fn any_running(&self) -> bool {
+        !self.paused_all && self.timers.all().iter().any(Timer::is_running)
+    }
+
+    /// Hold a sleep inhibitor while a timer is running and release it otherwise
+    fn update_inhibitor(&mut self) {
+        let wanted = self.keep_awake && self.any_running();
+        if !wanted {
+            self.inhibitor = None;
+            return;
+        }
+        if self.keep_awake_error.is_some() {
+            return;
+        }
+        match self.inhibitor.as_mut().map(Inhibitor::is_held) {
+            Some(true) => {}
+            Some(false) => {
+                self.inhibitor = None;
+                self.keep_awake_error = Some("keep awake: inhibitor exited".to_string());
+            }
+            None => match Inhibitor::acquire() {
+                Ok(inhibitor) => self.inhibitor = Some(inhibitor),
+                Err(error) => self.keep_awake_error = Some(format!("keep awake: {error}")),
+            },
+        }
+    }
+
+    /// Run a countdown without the TUI, overwriting a single line of stdout until it finishes
+    pub fn run_quiet(&mut self) -> Result<()> {
+        let frame_rate = self
+            .frame_rate
+            .unwrap_or(Duration::from_secs_f64(1.0 / 10.0));
+        let mut stdout = stdout();
+
+        self.exit_on_finish = true;
+        // There are no keys to stay open with, so there's nothing to wait for
+        self.grace = Duration::ZERO;
+        self.last_tick = Some(Instant::now());
+        while !self.exit {
+            write!(stdout, "\r{} ", self.quiet_line())?;
+            stdout.flush()?;
+            thread::sleep(frame_rate);
+            self.apply_commands();
+            self.catch_up();
+            self.publish_status();
+        }
+        writeln!(stdout, "\r{}", self.quiet_line())?;
+        Ok(())
+    }
+
+    /// The time as [`Timers::run_quiet`] writes it, rounded and split into days just as the big
+    /// digits would be
+    fn quiet_line(&self) -> String {
+        let timer = self.selected();
+        // Without colors a lead-in is told apart by counting up to zero from below it
+        let time = match timer.lead_in_after(Duration::ZERO) {
+            Some(lead_in) => format!("-{}", self.format_shown(lead_in, timer.mode())),
+            None => self.format_shown(timer.value(), timer.mode()),
+        };
+        self.numerals.apply(&time)
+    }
+
+    fn render_frame(&mut self, frame: &mut Frame) {
+        let state = &mut TimersState { theme: self.theme };
+
+        frame.render_stateful_widget(self, frame.area(), state);
+    }
+
+    /// Draw a frame the size of `area` into a fresh buffer instead of a terminal, just as
+    /// [`Timers::run`] would draw it
+    pub fn render_to_buffer(&mut self, area: Rect) -> Buffer {
+        let mut buf = Buffer::empty(area);
+        let mut state = TimersState { theme: self.theme };
+        self.render(area, &mut buf, &mut state);
+        buf
+    }
+
+    fn handle_events(&mut self) -> Result<()> {
+        self.apply_commands();
+        if event::poll(self.frame_interval())? {
+            if let Event::Key(key_event) = event::read()? {
+                self.handle_key(key_event);
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply whatever a key press is bound to
+    fn handle_key(&mut self, key_event: KeyEvent) {
+        // Any key counts as activity, even one that isn't bound to anything
+        self.mark_active();
+        let action = match (key_event.code, std::mem::take(&mut self.choosing_slot)) {
+            (KeyCode::Char(digit @ '1'..='9'), true) => Some(Action::SaveSlot(slot_number(digit))),
+            _ => Action::from_key(key_event),
+        };
+        // A key pressed while waiting to exit only keeps the app open, unless it is one to quit
+        if self.acknowledge_grace() && action != Some(Action::Quit) {
+            return;
+        }
+        self.banner = None;
+        if let Some(action) = action {
+            self.catch_up();
+            self.apply(action);
+        }
+    }
+
+    /// How long to wait for a key press before drawing the next frame
+    ///
+    /// Showing only hours and minutes, nothing changes until one of the timers reaches its next
+    /// minute, so there's no need to draw before then unless something else is moving or
+    /// commands may arrive from outside.
+    fn frame_interval(&self) -> Duration {
+        let frame_rate = self
+            .frame_rate
+            .unwrap_or(Duration::from_secs_f64(1.0 / 60.0));
+        let animating = self.timers.all().iter().any(Timers::alarm_active)
+            || self
+                .halfway_at
+                .is_some_and(|at| at.elapsed() < HALFWAY_NOTE)
+            || self.slot_note().is_some()
+            || (self.pulse && self.any_running())
+            || self.banner.is_some()
+            || self.exit_at.is_some();
+        if self.precision != Precision::Minutes
+            || animating
+            || self.commands.is_some()
+            || self.status.is_some()
+        {
+            return frame_rate;
+        }
+        let minute = Duration::from_secs(60);
+        self.timers
+            .all()
+            .iter()
+            .filter(|timer| timer.is_advancing() && !self.paused_all)
+            .map(|timer| {
+                let into_minute = Duration::from_nanos(
+                    (self.display_value(timer).as_nanos() % minute.as_nanos()) as u64,
+                );
+                let until_next = match timer.mode() {
+                    Mode::Countdown(_) if into_minute.is_zero() => minute,
+                    Mode::Countdown(_) => into_minute,
+                    _ => minute - into_minute,
+                };
+                // Just past the change, so the new minute is what gets drawn
+                until_next + Duration::from_millis(1)
+            })
+            .min()
+            .unwrap_or(minute)
+            .max(frame_rate)
+    }
+
+    /// Update the timers in response to an [`Action`]
+    pub fn apply(&mut self, action: Action) {
+        match action {
+            Action::Toggle => {
+                let timer = self.timers.selected_mut();
+                let was_running = timer.is_running();
+                timer.toggle();
+                match (was_running, timer.is_running()) {
+                    (false, true) => Timers::play(&self.sounds.start),
+                    (true, false) => Timers::play(&self.sounds.pause),
+                    _ => {}
+                }
+            }
+            Action::Reset => {
+                self.reset_workout();
+                self.timers.selected_mut().reset();
+            }
+            Action::Lap => {
+                let timer = self.timers.selected_mut();
+                let laps = timer.laps().len();
+                timer.lap();
+                if timer.laps().len() > laps {
+                    Timers::play(&self.sounds.lap);
+                }
+            }
+            Action::SwitchMode => self.timers.selected_mut().switch_mode(),
+            Action::Set(value) => self.timers.selected_mut().set_value(value),
+            Action::SelectNext => self.timers.select_next(),
+            Action::SelectPrevious => self.timers.select_previous(),
+            Action::PauseAll => self.paused_all = !self.paused_all,
+            Action::Invert => {
+                self.theme = self.theme.inverted();
+                self.inverted = !self.inverted;
+            }
+            Action::Collapse => self.collapsed = !self.collapsed,
+            Action::Banners => {
+                self.banners_on = !self.banners_on;
+                self.banner = None;
+            }
+            Action::Detach => {
+                self.exit = true;
+                self.detached = true;
+            }
+            Action::ChooseSaveSlot if self.slots.is_some() => {
+                self.choosing_slot = true;
+                self.slot_note = Some(("save to slot 1-9".to_string(), Instant::now()));
+            }
+            Action::ChooseSaveSlot => {}
+            Action::SaveSlot(slot) => self.save_slot(slot),
+            Action::LoadSlot(slot) => self.load_slot(slot),
+            Action::Quit => self.exit = true,
+        }
+    }
+
+    /// Whether the app was left with [`Action::Detach`], so the timers should be handed to a
+    /// background process
+    pub fn is_detached(&self) -> bool {
+        self.detached
+    }
+
+    /// Every timer being shown
+    pub fn timers(&self) -> &[Timer] {
+        self.timers.all()
+    }
+
+    /// The timer that key presses act on
+    pub fn selected(&self) -> &Timer {
+        self.timers.selected()
+    }
+
+    /// The value currently shown by the selected timer
+    pub fn timer(&self) -> Duration {
+        self.selected().value()
+    }
+
+    /// Whether [`Action::Quit`] has been applied or a countdown exited on finishing
+    pub fn should_exit(&self) -> bool {
+        self.exit
+    }
+
+    /// Whether the alarm effect for `timer` is currently in its "on" phase
+    fn alarm_active(timer: &Timer) -> bool {
+        timer.finished_at().is_some_and(|finished_at| {
+            let phase = finished_at.elapsed().as_millis() / ALARM_PHASE.as_millis();
+            phase < u128::from(ALARM_FLASHES) * 2 && phase.is_multiple_of(2)
+        })
+    }
+
+    /// Whether the background is in the on phase of the flash for passing halfway
+    fn halfway_flash_active(&self) -> bool {
+        self.halfway_at.is_some_and(|halfway_at| {
+            let phase = halfway_at.elapsed().as_millis() / ALARM_PHASE.as_millis();
+            phase < u128::from(HALFWAY_FLASHES) * 2 && phase.is_multiple_of(2)
+        })
+    }
+
+    /// Put a banner up in place of the digits if banners are on, for `timeout` or until a key
+    /// is pressed
+    fn show_banner(&mut self, text: String, timeout: Option<Duration>) {
+        if self.banners_on {
+            self.banner = Some((text, Instant::now(), timeout));
+        }
+    }
+
+    /// The banner to show in place of the digits, if one is up and hasn't timed out
+    fn active_banner(&self) -> Option<&str> {
+        let (text, shown_at, timeout) = self.banner.as_ref()?;
+        match timeout {
+            Some(timeout) if shown_at.elapsed() >= *timeout => None,
+            _ => Some(text),
+        }
+    }
+
+    /// Draw `text` as big as it fits, in the middle of `area`
+    fn render_banner(&self, text: &str, area: Rect, buf: &mut Buffer) {
+        match self.fitting_digit_size(area, text, 0) {
+            Some(digit_size) => BigText::builder()
+                .pixel_size(digit_size.into())
+                .lines(vec![Line::from(text.to_string()).style(
+                    Style::new().add_modifier(self.digit_modifier | Modifier::BOLD),
+                )])
+                .centered()
+                .build()
+                .render(center_vertical(area, digit_size.height()), buf),
+            None => Line::from(text.to_string())
+                .bold()
+                .centered()
+                .render(center_vertical(area, 1), buf),
+        }
+    }
+
+    /// The note that a countdown has passed halfway, while it is still showing
+    fn halfway_note(&self) -> Option<Line<'static>> {
+        let shown_for = self.halfway_at?.elapsed();
+        if shown_for >= HALFWAY_NOTE {
+            return None;
+        }
+        let note = Line::from("halfway").centered();
+        Some(if shown_for >= HALFWAY_NOTE / 2 {
+            note.add_modifier(Modifier::DIM)
+        } else {
+            note.bold()
+        })
+    }
+
+    /// The number of rows the content inside the block needs, used to center it vertically
+    fn content_height(&self, digit_size: DigitSize) -> u16 {
+        digit_size.height() + self.bar_height() + self.watermark_height() + self.laps_height()
+    }
+
+    /// How much of the selected countdown has elapsed, if the watermark is being drawn
+    fn watermark_value(&self) -> Option<Duration> {
+        let timer = self.selected();
+        match timer.mode() {
+            Mode::Countdown(duration) if self.watermark => {
+                Some(duration.saturating_sub(self.display_value(timer)))
+            }
+            _ => None,
+        }
+    }
+
+    /// The rows taken up by the watermark, including the gap above it
+    fn watermark_height(&self) -> u16 {
+        match self.watermark_value() {
+            Some(_) => 1 + WATERMARK_SIZE.height(),
+            None => 0,
+        }
+    }
+
+    /// Draw the elapsed time dimly in the smallest digits, or as plain text if they don't fit
+    fn render_watermark(&self, elapsed: Duration, area: Rect, buf: &mut Buffer) {
+        let text = self.format_shown(elapsed, Mode::Stopwatch);
+        let [_, area] = Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(area);
+        let chars = text.chars().count() as u16;
+        if WATERMARK_SIZE.width().saturating_mul(chars) <= area.width {
+            BigText::builder()
+                .pixel_size(WATERMARK_SIZE.into())
+                .lines(vec![Line::from(text)])
+                .style(Style::new().add_modifier(Modifier::DIM))
+                .alignment(self.align.into())
+                .build()
+                .render(area, buf);
+        } else {
+            Line::from(text)
+                .dim()
+                .alignment(self.align.into())
+                .render(center_vertical(area, 1), buf);
+        }
+    }
+
+    /// The row under the digits taken up by the clock's sweep or the sub-second bar
+    fn bar_height(&self) -> u16 {
+        u16::from(self.sweep_shown() || self.subsecond_bar_shown())
+    }
+
+    fn sweep_shown(&self) -> bool {
+        self.sweep && self.selected().mode() == Mode::Clock
+    }
+
+    fn subsecond_bar_shown(&self) -> bool {
+        // Focus mode and clocks don't show any of the fraction
+        self.precision == Precision::Bar && !self.focus && self.selected().mode() != Mode::Clock
+    }
+
+    /// Draw the clock's sweep under all of the digits, or the sub-second bar under the seconds
+    ///
+    /// `digits_area` and `digit_size` are where and how big the `chars` long time was drawn.
+    fn render_bar(
+        &self,
+        value: Duration,
+        digits_area: Rect,
+        digit_size: DigitSize,
+        chars: u16,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        let width = digit_size.width();
+        let text_width = width.saturating_mul(chars);
+        let left = self.digits_left(digits_area, text_width);
+        let (x, bar_width, fraction) = if self.sweep_shown() {
+            let through_minute = (value.as_secs_f64() % 60.0) / 60.0;
+            (left, text_width, through_minute)
+        } else {
+            let seconds_left = left + width.saturating_mul(chars.saturating_sub(2));
+            let through_second = f64::from(value.subsec_millis()) / 1000.0;
+            (seconds_left, width.saturating_mul(2), through_second)
+        };
+        let bar_area = Rect::new(x, area.y, bar_width, area.height).intersection(area);
+        fill_line(fraction, bar_area.width).render(bar_area, buf);
+    }
+
+    /// The column the big digits start at when they are `text_width` wide, matching how the big
+    /// text lines itself up
+    fn digits_left(&self, digits_area: Rect, text_width: u16) -> u16 {
+        let offset = match self.align {
+            Align::Left => 0,
+            Align::Center => (digits_area.width / 2).saturating_sub(text_width / 2),
+            Align::Right => digits_area.width.saturating_sub(text_width),
+        };
+        digits_area.x + offset
+    }
+
+    /// Draw the pulsing dot one column after the digits, level with their middle
+    fn render_pulse(
+        &self,
+        value: Duration,
+        digits_area: Rect,
+        digit_size: DigitSize,
+        chars: u16,
+        buf: &mut Buffer,
+    ) {
+        let text_width = digit_size.width().saturating_mul(chars);
+        let x = self.digits_left(digits_area, text_width) + text_width + 1;
+        let y = digits_area.y + digit_size.height() / 2;
+        if !digits_area.contains(Position::new(x, y)) {
+            return;
+        }
+        // Brightest on each whole second, fading out and back in between
+        let through_second = f64::from(value.subsec_millis()) / 1000.0;
+        let brightness = (1.0 + (through_second * TAU).cos()) / 2.0;
+        let style = match (self.theme.fg, self.theme.bg) {
+            (Color::Rgb(fr, fg, fb), Color::Rgb(br, bg, bb)) => {
+                let mix = |from: u8, to: u8| {
+                    (f64::from(to) + (f64::from(from) - f64::from(to)) * brightness).round() as u8
+                };
+                Style::new().fg(Color::Rgb(mix(fr, br), mix(fg, bg), mix(fb, bb)))
+            }
+            // Named colors can't be blended, so they only go dim and bright
+            _ if brightness < 0.5 => Style::new().add_modifier(Modifier::DIM),
+            _ => Style::new().add_modifier(Modifier::BOLD),
+        };
+        buf[(x, y)].set_symbol("●").set_style(style);
+    }
+
+    /// The rows taken up by the lap list, including the gap above it and the lap in progress
+    fn laps_height(&self) -> u16 {
+        match self.selected().laps().len().min(LAPS_SHOWN) {
+            0 => 0,
+            shown => shown as u16 + 2,
+        }
+    }
+
+    /// One line for the lap in progress followed by one per recent lap, newest first, each with
+    /// its split and how long it took
+    ///
+    /// The quickest lap is highlighted, and the lap in progress is green while it is quicker
+    /// than that and red once it is slower.
+    fn lap_lines(&self) -> Vec<Line<'static>> {
+        let timer = self.selected();
+        let laps = timer.laps();
+        if laps.is_empty() {
+            return Vec::new();
+        }
+        let best = timer.best_lap();
+
+        let value = self.display_value(timer);
+        let current_time = timer.current_lap_time(value);
+        let split = format_duration(value, &self.format_options());
+        let delta = format_duration(current_time, &self.format_options());
+        let mut current = Line::from(format!("Now  {:>3}  {split}  +{delta}", laps.len() + 1));
+        // With a single lap there is nothing quicker to compare against yet
+        let pace = best.or((laps.len() == 1).then_some(0));
+        if let Some(pace) = pace.map(|index| timer.lap_time(index)) {
+            current = current.fg(if current_time <= pace {
+                Color::Green
+            } else {
+                Color::Red
+            });
+        }
+
+        let recorded = (0..laps.len()).rev().take(LAPS_SHOWN).map(|index| {
+            let lap = laps[index];
+            let split = format_duration(lap.value, &self.format_options());
+            let delta = format_duration(timer.lap_time(index), &self.format_options());
+            let kind = if lap.auto { "Auto" } else { "Lap" };
+            let mut line = Line::from(format!("{kind:<4} {:>3}  {split}  +{delta}", index + 1));
+            let planned = self.splits.get(index);
+            if let (Some(&planned), Some(actual)) = (planned, timer.measured_at(lap.value)) {
+                line.push_span(self.split_difference(actual, planned));
+            }
+            if best == Some(index) {
+                line.fg(Color::Cyan).bold()
+            } else {
+                line
+            }
+        });
+        std::iter::once(current).chain(recorded).collect()
+    }
+
+    /// How far ahead of or behind the planned split a lap was, green when on or ahead of pace
+    fn split_difference(&self, actual: Duration, planned: Duration) -> Span<'static> {
+        let (sign, difference, color) = if actual <= planned {
+            ("-", planned - actual, Color::Green)
+        } else {
+            ("+", actual - planned, Color::Red)
+        };
+        let difference = format_duration(difference, &self.format_options());
+        Span::from(format!("  {sign}{difference}")).fg(color)
+    }
+
+    /// A bar per recent lap, newest first to line up with [`Timers::lap_lines`], with the longest
+    /// lap filling the width
+    fn lap_chart(&self) -> BarChart<'static> {
+        let timer = self.selected();
+        let bars: Vec<Bar> = (0..timer.laps().len())
+            .rev()
+            .take(LAPS_SHOWN)
+            .map(|index| {
+                let time = timer.lap_time(index);
+                let bar = Bar::default()
+                    .value(time.as_millis() as u64)
+                    // The lap list beside the chart already has the times in full
+                    .text_value(String::new())
+                    .label(Line::from(format!("{:>3}", index + 1)));
+                if timer.best_lap() == Some(index) {
+                    bar.style(Color::Cyan)
+                } else {
+                    bar
+                }
+            })
+            .collect();
+        BarChart::default()
+            .direction(Direction::Horizontal)
+            .bar_width(1)
+            .bar_gap(0)
+            .data(BarGroup::default().bars(&bars))
+    }
+
+    /// The time shown for `timer`, which focus mode and clocks always show to the second, or
+    /// what is left of its lead-in
+    fn display_text(&self, timer: &Timer) -> String {
+        let text = match self.lead_in_value(timer) {
+            Some(lead_in) => self.format_shown(lead_in, Mode::Countdown(lead_in)),
+            None => self.format_shown(self.display_value(timer), timer.mode()),
+        };
+        if !self.fixed_width {
+            return text;
+        }
+        // Padded on the left, so the seconds stay put as the hours lose a digit
+        let width = self.widest_text(timer);
+        format!("{text:>width$}")
+    }
+
+    /// The most characters `timer` is expected to take up, which is as wide as its full
+    /// countdown or, for anything counting up, as wide as zero
+    fn widest_text(&self, timer: &Timer) -> usize {
+        let width = |value, mode| self.format_shown(value, mode).chars().count();
+        let longest = match timer.mode() {
+            Mode::Countdown(duration) => duration,
+            _ => Duration::ZERO,
+        };
+        let lead_in = timer.lead_in();
+        width(Duration::ZERO, timer.mode())
+            .max(width(longest, timer.mode()))
+            .max(width(lead_in, Mode::Countdown(lead_in)))
+    }
+
+    /// How much of `timer`'s lead-in is left, if it is still counting it down
+    fn lead_in_value(&self, timer: &Timer) -> Option<Duration> {
+        match self.last_tick {
+            Some(last_tick) if !self.paused_all => timer.lead_in_after(last_tick.elapsed()),
+            _ => timer.lead_in_after(Duration::ZERO),
+        }
+    }
+
+    /// How times other than the big digits are written, such as laps and the title
+    fn format_options(&self) -> FormatOptions {
+        FormatOptions {
+            precision: self.precision,
+            round: false,
+            days: self.days,
+        }
+    }
+
+    /// Format `value` the way a timer in `mode` is shown
+    fn format_shown(&self, value: Duration, mode: Mode) -> String {
+        let precision = if self.precision == Precision::Minutes {
+            Precision::Minutes
+        } else if self.focus || mode == Mode::Clock {
+            Precision::Seconds
+        } else {
+            self.precision
+        };
+        let options = FormatOptions {
+            precision,
+            round: self.round,
+            days: self.days,
+        };
+        format_duration(value, &options)
+    }
+
+    /// The line of big digits, split so focus mode can dim everything after the minutes, and
+    /// in a different color while `timer` is in its lead-in, in a workout phase with a color of
+    /// its own or counting an overrun
+    fn digit_line(&self, timer: &Timer, text: String) -> Line<'static> {
+        let mut style = Style::new().add_modifier(self.digit_modifier);
+        if self.lead_in_value(timer).is_some() {
+            style = style.fg(LEAD_IN_COLOR);
+        } else if let Some(color) = self.current_phase().and_then(|phase| phase.color) {
+            style = style.fg(color);
+        } else if timer.overrun().is_some()
+            && !(self.alarm_effect == AlarmEffect::Flash && Timers::alarm_active(timer))
+        {
+            // Left alone while the background flashes, which could be in the same color
+            style = style.fg(OVERRUN_COLOR);
+        }
+        if !self.focus {
+            return Line::from(text).style(style);
+        }
+        let minutes_end = text.rfind(':').unwrap_or(text.len());
+        let (coarse, fine) = text.split_at(minutes_end);
+        Line::from(vec![
+            coarse.to_string().bold(),
+            fine.to_string().add_modifier(Modifier::DIM),
+        ])
+        .style(style)
+    }
+
+    /// The largest digit size no bigger than the chosen one that fits `text` into `area`, with
+    /// `extra_rows` to spare underneath
+    fn fitting_digit_size(&self, area: Rect, text: &str, extra_rows: u16) -> Option<DigitSize> {
+        if !self.numerals.has_big_digits() {
+            return None;
+        }
+        let chars = text.chars().count() as u16;
+        DigitSize::LARGEST_FIRST
+            .into_iter()
+            .filter(|size| {
+                size.width() <= self.digit_size.width() && size.height() <= self.digit_size.height()
+            })
+            .find(|&size| {
+                size.width().saturating_mul(chars) <= area.width
+                    && size.height() + extra_rows <= area.height
+            })
+    }
+
+    /// Advance every timer by `elapsed` unless they are all being held, in which case it only
+    /// counts as time spent paused
+    pub fn tick(&mut self, elapsed: Duration) {
+        if self.paused_all {
+            for timer in self.timers.all_mut() {
+                timer.hold(elapsed);
+            }
+            return;
+        }
+        self.announce_phase();
+        if self.timers.all().iter().any(Timer::is_advancing) {
+            self.ticks = self.ticks.wrapping_add(1);
+        }
+        let events: Vec<TimerEvent> = self
+            .timers
+            .all_mut()
+            .iter_mut()
+            .flat_map(|timer| timer.tick(elapsed))
+            .collect();
+        for event in events {
+            match event {
+                TimerEvent::Finished if self.next_phase() => {}
+                TimerEvent::Finished => {
+                    self.show_banner(self.banners.done.clone(), None);
+                    self.finish();
+                }
+                TimerEvent::AutoPaused | TimerEvent::FinalSecond => {
+                    let _ = CompletionAction::Bell.run();
+                }
+                TimerEvent::Halfway if self.halfway => {
+                    self.halfway_at = Some(Instant::now());
+                    if self.halfway_bell {
+                        let _ = CompletionAction::Bell.run();
+                    }
+                }
+                TimerEvent::Halfway => {}
+                TimerEvent::LeadInOver => {
+                    self.show_banner(self.banners.go.clone(), Some(self.banners.go_for));
+                    // Twice, to tell the start apart from the bells counting down to it
+                    let _ = CompletionAction::Bell.run();
+                    let _ = CompletionAction::Bell.run();
+                }
+            }
+        }
+    }
+
+    /// Run the configured finish actions after a countdown has reached zero
+    fn finish(&mut self) {
+        Timers::play(&self.sounds.finish);
+
+        // Every action gets its chance even if an earlier one failed
+        let failed: Vec<&str> = self
+            .completion_actions
+            .iter()
+            .filter(|action| action.run().is_err())
+            .map(CompletionAction::name)
+            .collect();
+        self.completion_error =
+            (!failed.is_empty()).then(|| format!("failed: {}", failed.join(", ")));
+        self.next_alarm();
+
+        let all_finished = self
+            .timers
+            .all()
+            .iter()
+            .filter(|timer| matches!(timer.mode(), Mode::Countdown(_)))
+            .all(Timer::is_finished);
+        if self.exit_on_finish && all_finished {
+            if self.grace.is_zero() {
+                self.exit = true;
+            } else {
+                self.exit_at = Some(Instant::now() + self.grace);
+                self.grace_bells = 0;
+            }
+        }
+    }
+
+    /// Draw the selected timer as big digits with its laps underneath
+    fn render_single(&self, area: Rect, buf: &mut Buffer) {
+        let timer = self.selected();
+        let text = self.display_text(timer);
+        let show_digits = !(Timers::alarm_active(timer) && self.alarm_effect == AlarmEffect::Blink);
+
+        let chars = text.chars().count() as u16;
+        let extra_rows = self.bar_height() + self.watermark_height() + self.laps_height();
+        match self.fitting_digit_size(area, &text, extra_rows) {
+            Some(digit_size) => {
+                let timer_text = BigText::builder()
+                    .pixel_size(digit_size.into())
+                    .lines(vec![self.digit_line(timer, text)])
+                    .alignment(self.align.into())
+                    .build();
+
+                let content_area = center_vertical(area, self.content_height(digit_size));
+                if self.pie {
+                    self.render_pie(timer, area, content_area.y, buf);
+                }
+                let [digits_area, bar_area, watermark_area, laps_area] = Layout::vertical([
+                    Constraint::Length(digit_size.height()),
+                    Constraint::Length(self.bar_height()),
+                    Constraint::Length(self.watermark_height()),
+                    Constraint::Length(self.laps_height()),
+                ])
+                .areas(content_area);
+                if show_digits {
+                    timer_text.render(digits_area, buf);
+                    self.orient_digits(digits_area, buf);
+                }
+                if self.bar_height() > 0 {
+                    let value = self.display_value(timer);
+                    self.render_bar(value, digits_area, digit_size, chars, bar_area, buf);
+                }
+                if self.pulse {
+                    let value = self.display_value(timer);
+                    self.render_pulse(value, digits_area, digit_size, chars, buf);
+                }
+                if let Some(elapsed) = self.watermark_value() {
+                    self.render_watermark(elapsed, watermark_area, buf);
+                }
+                let (laps_area, chart_area) = if self.lap_chart && self.laps_height() > 0 {
+                    let [list, chart] =
+                        Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)])
+                            .spacing(2)
+                            .areas(laps_area);
+                    (list, Some(chart))
+                } else {
+                    (laps_area, None)
+                };
+                let lap_rows = laps_area.rows().skip(1);
+                for (line, row) in self.lap_lines().into_iter().zip(lap_rows) {
+                    match chart_area {
+                        Some(_) => line.right_aligned().render(row, buf),
+                        None => line.alignment(self.align.into()).render(row, buf),
+                    }
+                }
+                if let Some(chart_area) = chart_area {
+                    // Starts level with the newest recorded lap, below the gap and the lap in
+                    // progress
+                    let [_, chart_area] =
+                        Layout::vertical([Constraint::Length(2), Constraint::Fill(1)])
+                            .areas(chart_area);
+                    self.lap_chart().render(chart_area, buf);
+                }
+            }
+            None => {
+                // Too small for even the smallest big digits, so fall back to plain text with a
+                // hint underneath when there is room for it
+                let [time_area, hint_area] =
+                    Layout::vertical([Constraint::Length(1), Constraint::Length(1)])
+                        .flex(Flex::Center)
+                        .areas(area);
+                if show_digits {
+                    Line::from(self.numerals.apply(&text))
+                        .alignment(self.align.into())
+                        .render(time_area, buf);
+                }
+                if self.numerals.has_big_digits() {
+                    Line::from("terminal too small")
+                        .centered()
+                        .italic()
+                        .render(hint_area, buf);
+                }
+            }
+        }
+    }
+
+    /// Draw every timer's label, time and state on the top row of `area`
+    fn render_collapsed(&self, area: Rect, buf: &mut Buffer, theme: Theme) {
+        let timers = self.timers.all();
+        let mut spans: Vec<Span> = Vec::new();
+        for (index, timer) in timers.iter().enumerate() {
+            if index > 0 {
+                spans.push(" | ".into());
+            }
+            let state = if timer.is_finished() {
+                "finished"
+            } else if timer.is_advancing() && !self.paused_all {
+                "running"
+            } else {
+                "paused"
+            };
+            let label = timer.label().unwrap_or(timer.mode().name());
+            let time = Span::from(self.numerals.apply(&self.display_text(timer)));
+            let time = if timers.len() > 1 && index == self.timers.selected_index() {
+                time.bold()
+            } else {
+                time
+            };
+            spans.extend([format!("{label} ").into(), time, format!(" {state}").into()]);
+        }
+        spans.extend([" Expand ".into(), "<C>".bold()]);
+
+        let row = Rect::new(area.x, area.y, area.width, area.height.min(1));
+        Block::new().bg(theme.bg).fg(theme.fg).render(row, buf);
+        Line::from(spans).render(row, buf);
+    }
+
+    /// Draw every timer in its own row, each with its label above smaller digits
+    fn render_segments(&self, area: Rect, buf: &mut Buffer) {
+        let timers = self.timers.all();
+        let rows = Layout::vertical(timers.iter().map(|_| Constraint::Fill(1))).split(area);
+
+        for (index, (timer, &row)) in timers.iter().zip(rows.iter()).enumerate() {
+            let selected = index == self.timers.selected_index();
+            let text = self.display_text(timer);
+            let show_digits =
+                !(Timers::alarm_active(timer) && self.alarm_effect == AlarmEffect::Blink);
+
+            let name = timer
+                .label()
+                .map_or_else(|| format!("Timer {}", index + 1), str::to_string);
+            let label = if selected {
+                Line::from(format!("▶ {name} ◀")).bold().reversed()
+            } else {
+                Line::from(name)
+            };
+
+            let digit_size = self.fitting_digit_size(row, &text, 1);
+            let height = 1 + digit_size.map_or(1, DigitSize::height);
+            let [label_area, digits_area] =
+                Layout::vertical([Constraint::Length(1), Constraint::Fill(1)])
+                    .areas(center_vertical(row, height));
+
+            label.alignment(self.align.into()).render(label_area, buf);
+            if !show_digits {
+                continue;
+            }
+            match digit_size {
+                Some(digit_size) => {
+                    BigText::builder()
+                        .pixel_size(digit_size.into())
+                        .lines(vec![self.digit_line(timer, text)])
+                        .alignment(self.align.into())
+                        .build()
+                        .render(digits_area, buf);
+                    self.orient_digits(digits_area, buf);
+                }
+                None => Line::from(self.numerals.apply(&text))
+                    .alignment(self.align.into())
+                    .render(digits_area, buf),
+            }
+        }
+    }
+}
+
+impl Timers {
+    /// The key hints for the block title, as many as fit in `width` columns
+    ///
+    /// Toggling, resetting and exiting are always given, followed by as many of the others as
+    /// there is room for, selecting first when there are several timers to select from.
+    fn instructions(width: u16, segmented: bool) -> Line<'static> {
+        let hint_width = |(name, key): (&str, &str)| name.len() + key.len() + 2;
+        let mut extras = vec![
+            ("Lap", "<L>"),
+            ("Pause all", "<P>"),
+            ("Invert", "<I>"),
+            ("Mode", "<M>"),
+            ("Detach", "<D>"),
+            ("Collapse", "<C>"),
+        ];
+        if segmented {
+            extras.insert(0, ("Select", "<Up/Down>"));
+        }
+        let mut hints = vec![("Toggle", "<Space>"), ("Reset", "<R>")];
+        let exit = ("Exit", "<Escape>");
+        let mut used = hints.iter().copied().map(hint_width).sum::<usize>() + hint_width(exit) - 1;
+        for hint in extras {
+            if used + hint_width(hint) <= usize::from(width) {
+                used += hint_width(hint);
+                hints.push(hint);
+            }
+        }
+        hints.push(exit);
+
+        let mut spans = Vec::new();
+        for (index, (name, key)) in hints.into_iter().enumerate() {
+            let gap = if index == 0 { "" } else { " " };
+            spans.push(format!("{gap}{name} ").into());
+            spans.push(key.bold());
+        }
+        Line::from(spans)
+    }
+}
+
+impl StatefulWidget for &mut Timers {
+    type State = TimersState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        if self.collapsed {
+            self.render_collapsed(area, buf, state.theme);
+            return;
+        }
+        let segmented = self.timers.all().len() > 1;
+
+        let instructions = Timers::instructions(area.width.saturating_sub(2), segmented);
+
+        let flashing = (self.alarm_effect == AlarmEffect::Flash
+            && self.timers.all().iter().any(Timers::alarm_active))
+            || self.halfway_flash_active();
+        let bg = if flashing {
+            state.theme.alarm
+        } else {
+            state.theme.bg
+        };
+
+        let mut block = Block::new().bg(bg).fg(state.theme.fg);
+
+        block = match self.title_position {
+            TitlePosition::Top => block.title_top(instructions.centered()),
+            TitlePosition::Bottom => block.title_bottom(instructions.centered()),
+        };
+
+        // Stacked timers carry their own labels, so only a single timer's mode and label go in
+        // the title
+        if !segmented {
+            let timer = self.selected();
+            let mode = match self.lead_in_value(timer) {
+                Some(_) => "lead-in",
+                None => timer.mode().name(),
+            };
+            let mut title = match timer.label() {
+                Some(label) => format!("[{mode}] {label}"),
+                None => format!("[{mode}]"),
+            };
+            let progress = timer.progress(self.display_value(timer));
+            if let (true, Some(progress)) = (self.percent, progress) {
+                // Rounded down so 100% only shows once it is really done
+                title.push_str(&format!(" {}%", (progress * 100.0).floor()));
+            }
+            if !self.workout.is_empty() {
+                title.push_str(&format!(" {}/{}", self.phase + 1, self.workout.len()));
+            }
+            if let Some(&alarm) = self.alarms.first() {
+                let alarm: DateTime<Local> = alarm.into();
+                title.push_str(&format!(" until {}", alarm.format("%H:%M")));
+                if self.alarms.len() > 1 {
+                    title.push_str(&format!(" (+{} more)", self.alarms.len() - 1));
+                }
+            }
+            if self.show_paused && !timer.paused_for().is_zero() {
+                let paused = format_duration(timer.paused_for(), &Precision::Seconds.into());
+                title.push_str(&format!(" paused {paused}"));
+            }
+            let label = Line::from(title).centered();
+            block = match self.title_position {
+                TitlePosition::Top => block.title_bottom(label),
+                TitlePosition::Bottom => block.title_top(label),
+            };
+        }
+
+        // The block goes first so its colors don't paint over any the content sets itself
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if let Some(banner) = self.active_banner() {
+            self.render_banner(banner, inner, buf);
+        } else if segmented {
+            self.render_segments(inner, buf);
+        } else {
+            self.render_single(inner, buf);
+        }
+        if let (Some(note), true) = (self.halfway_note(), inner.height >= 1) {
+            let row = Rect::new(inner.x, inner.bottom() - 1, inner.width, 1);
+            note.render(row, buf);
+        }
+
+        let mut status = Vec::new();
+        if self.paused_all {
+            status.push("PAUSED");
+        }
+        if let Some(error) = &self.keep_awake_error {
+            status.push(error);
+        }
+        if let Some(error) = &self.completion_error {
+            status.push(error);
+        }
+        if let Some(note) = self.slot_note() {
+            status.push(note);
+        }
+        let grace_note = self.grace_left().map(|left| {
+            let seconds = left.as_millis().div_ceil(1000);
+            format!("exiting in {seconds}s, any key to stay")
+        });
+        if let Some(note) = &grace_note {
+            status.push(note);
+        }
+        if !status.is_empty() && area.height >= 1 {
+            let status = status.join(" | ");
+            buf.set_stringn(
+                area.left() + 1,
+                area.top(),
+                status,
+                usize::from(area.width.saturating_sub(4)),
+                state.theme.fg,
+            );
+        }
+
+        let spinner = SPINNER[self.ticks / TICKS_PER_SPINNER_FRAME % SPINNER.len()];
+        if area.width >= 2 && area.height >= 1 {
+            buf.set_string(area.right() - 2, area.top(), spinner, state.theme.fg);
+        }
+    }
+}
+
+/// Quote a CSV field if it has anything in it that would otherwise split or end it
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// A bar `width` cells wide and `fraction` of the way full
+fn fill_line(fraction: f64, width: u16) -> Line<'static> {
+    let filled = (fraction * f64::from(width)).round() as usize;
+    let empty = usize::from(width).saturating_sub(filled);
+    Line::from(vec![
+        "━".repeat(filled).into(),
+        "─".repeat(empty).add_modifier(Modifier::DIM),
+    ])
+}
+
+fn center_vertical(area: Rect, height: u16) -> Rect {
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The symbols drawn in each row of `buf`, top to bottom
+    fn rows(buf: &Buffer) -> Vec<String> {
+        let area = buf.area;
+        (area.top()..area.bottom())
+            .map(|y| {
+                (area.left()..area.right())
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn content_height_matches_digit_size() {
+        let cases = [
+            (DigitSize::Full, 8),
+            (DigitSize::HalfHeight, 4),
+            (DigitSize::HalfWidth, 8),
+            (DigitSize::Quadrant, 4),
+            (DigitSize::ThirdHeight, 3),
+            (DigitSize::Sextant, 3),
+        ];
+        for (digit_size, height) in cases {
+            let timers = Timers::default().with_digit_size(digit_size);
+            assert_eq!(timers.content_height(digit_size), height, "{digit_size:?}");
+        }
+    }
+
+    #[test]
+    fn tiny_area_falls_back_to_plain_text() {
+        let buf = Timers::default().render_to_buffer(Rect::new(0, 0, 20, 5));
+        let rows = rows(&buf);
+        assert!(
+            rows.iter().any(|row| row.contains("00:00:00.000")),
+            "{rows:#?}"
+        );
+        assert!(
+            rows.iter().any(|row| row.contains("terminal too small")),
+            "{rows:#?}"
+        );
+    }
+
+    /// The modifiers of every drawn pixel of the digits, leaving out the title rows
+    fn digit_modifiers(mut timers: Timers) -> Vec<Modifier> {
+        let area = Rect::new(0, 0, 80, 10);
+        let buf = timers.render_to_buffer(area);
+        (1..area.height - 1)
+            .flat_map(|y| (0..area.width).map(move |x| (x, y)))
+            .map(|position| &buf[position])
+            .filter(|cell| cell.symbol() != " ")
+            .map(|cell| cell.modifier)
+            .collect()
+    }
+
+    #[test]
+    fn digit_modifiers_are_applied() {
+        let bold = digit_modifiers(Timers::default().with_digit_modifier(Modifier::BOLD));
+        assert!(!bold.is_empty());
+        assert!(bold
+            .iter()
+            .all(|modifier| modifier.contains(Modifier::BOLD)));
+
+        let both = Modifier::BOLD | Modifier::ITALIC;
+        let styled = digit_modifiers(Timers::default().with_digit_modifier(both));
+        assert!(styled.iter().all(|modifier| modifier.contains(both)));
+    }
+
+    #[test]
+    fn digits_are_plain_by_default() {
+        let plain = digit_modifiers(Timers::default());
+        assert!(!plain.is_empty());
+        assert!(plain.iter().all(|modifier| modifier.is_empty()));
+    }
+
+    #[test]
+    fn narrow_area_downgrades_digit_size() {
+        let timers = Timers::default().with_digit_size(DigitSize::Full);
+        let area = Rect::new(0, 0, 60, 6);
+        assert_eq!(
+            timers.fitting_digit_size(area, "00:00:00.000", 0),
+            Some(DigitSize::Quadrant)
+        );
+    }
+
+    #[test]
+    fn drawing_moves_on_between_ticks() {
+        let mut timers = Timers::default()
+            .with_timer(
+                Timer::new(Mode::Stopwatch)
+                    .with_elapsed(Duration::from_secs(10))
+                    .with_running(true),
+            )
+            .with_precision(Precision::Milliseconds);
+        timers.apply(Action::Collapse);
+        timers.last_tick = Instant::now().checked_sub(Duration::from_millis(250));
+        let row = rows(&timers.render_to_buffer(Rect::new(0, 0, 60, 1)))[0].clone();
+        assert!(row.contains("00:00:10.2"), "{row}");
+        assert_eq!(timers.selected().value(), Duration::from_secs(10));
+
+        let mut timers = Timers::default()
+            .with_timer(
+                Timer::new(Mode::Stopwatch)
+                    .with_elapsed(Duration::from_secs(10))
+                    .with_running(true),
+            )
+            .with_digit_size(DigitSize::Sextant)
+            .with_precision(Precision::Bar);
+        let area = Rect::new(0, 0, 40, 7);
+        timers.last_tick = Some(Instant::now());
+        let at_tick = rows(&timers.render_to_buffer(area));
+        timers.last_tick = Instant::now().checked_sub(Duration::from_millis(500));
+        let between_ticks = rows(&timers.render_to_buffer(area));
+        assert_eq!(at_tick[..5], between_ticks[..5]);
+        assert_eq!(at_tick[5].trim(), "────────");
+        assert_eq!(between_ticks[5].trim(), "━━━━────");
+    }
+
+    #[test]
+    fn quiet_output_is_rounded_too() {
+        let countdown = || {
+            Timer::new(Mode::Countdown(Duration::from_secs(2)))
+                .with_running(true)
+                .with_lead_in(Duration::from_secs(1))
+        };
+        let mut timers = Timers::default()
+            .with_timer(countdown())
+            .with_precision(Precision::Seconds)
+            .with_round(true);
+        timers.tick(Duration::from_millis(400));
+        assert_eq!(timers.quiet_line(), "-00:00:01");
+        timers.tick(Duration::from_millis(700));
+        assert_eq!(timers.quiet_line(), "00:00:02");
+
+        let mut truncated = Timers::default()
+            .with_timer(countdown())
+            .with_precision(Precision::Seconds);
+        truncated.tick(Duration::from_millis(1100));
+        assert_eq!(truncated.quiet_line(), "00:00:01");
+    }
+
+    #[test]
+    fn seconds_are_truncated_without_rounding() {
+        let timers = Timers::default()
+            .with_timer(Timer::new(Mode::Stopwatch).with_elapsed(Duration::from_millis(59_600)))
+            .with_precision(Precision::Seconds);
+        assert_eq!(timers.display_text(timers.selected()), "00:00:59");
+    }
+
+    #[test]
+    fn auto_contrast_picks_dark_text_on_light_backgrounds() {
+        let theme = Theme::default();
+        assert_eq!(
+            theme.with_auto_contrast(None, Some(Color::White)).fg,
+            Color::Black
+        );
+        assert_eq!(
+            theme.with_auto_contrast(None, Some(Color::Yellow)).fg,
+            Color::Black
+        );
+        assert_eq!(
+            theme
+                .with_auto_contrast(None, Some(Color::Rgb(238, 232, 213)))
+                .fg,
+            Color::Black
+        );
+        assert_eq!(
+            theme.with_auto_contrast(None, Some(Color::Indexed(231))).fg,
+            Color::Black
+        );
+    }
+
+    #[test]
+    fn auto_contrast_picks_light_text_on_dark_backgrounds() {
+        let theme = Theme::new(Color::Black, Color::White, Color::Red);
+        assert_eq!(
+            theme.with_auto_contrast(None, Some(Color::Blue)).fg,
+            Color::White
+        );
+        assert_eq!(
+            theme.with_auto_contrast(None, Some(Color::Red)).fg,
+            Color::White
+        );
+        assert_eq!(
+            theme
+                .with_auto_contrast(None, Some(Color::Rgb(0, 43, 54)))
+                .fg,
+            Color::White
+        );
+        assert_eq!(
+            theme.with_auto_contrast(None, Some(Color::Indexed(232))).fg,
+            Color::White
+        );
+    }
+
+    #[test]
+    fn auto_contrast_only_fills_in_a_missing_color() {
+        let theme = Theme::default();
+        let given = theme.with_auto_contrast(Some(Color::LightYellow), None);
+        assert_eq!((given.fg, given.bg), (Color::LightYellow, Color::Black));
+
+        let both = theme.with_auto_contrast(Some(Color::Red), Some(Color::Blue));
+        assert_eq!((both.fg, both.bg), (Color::Red, Color::Blue));
+
+        let reset = theme.with_auto_contrast(None, Some(Color::Reset));
+        assert_eq!((reset.fg, reset.bg), (Color::White, Color::Reset));
+    }
+
+    #[test]
+    fn lap_chart_bars_scale_to_the_longest_lap() {
+        let mut timers = Timers::default()
+            .with_timer(Timer::new(Mode::Stopwatch).with_running(true))
+            .with_lap_chart(true);
+        for millis in [2000, 4000] {
+            timers.tick(Duration::from_millis(millis));
+            timers.apply(Action::Lap);
+        }
+        let buf = timers.render_to_buffer(Rect::new(0, 0, 90, 16));
+
+        let bar_lengths: Vec<usize> = rows(&buf)
+            .into_iter()
+            .filter(|row| row.contains("Lap "))
+            .map(|row| row.matches('█').count())
+            .filter(|&length| length > 0)
+            .collect();
+        assert_eq!(bar_lengths.len(), 2, "{bar_lengths:?}");
+        assert_eq!(bar_lengths[0], bar_lengths[1] * 2, "{bar_lengths:?}");
+    }
+
+    #[test]
+    fn hours_and_minutes_are_redrawn_when_the_minute_changes() {
+        let timers = Timers::default()
+            .with_timer(
+                Timer::new(Mode::Stopwatch)
+                    .with_elapsed(Duration::from_secs(2 * 60 * 60 + 5 * 60 + 45))
+                    .with_running(true),
+            )
+            .with_precision(Precision::Minutes);
+        assert_eq!(timers.display_text(timers.selected()), "02:05");
+        assert_eq!(timers.frame_interval(), Duration::from_millis(15_001));
+
+        let paused = Timers::default().with_precision(Precision::Minutes);
+        assert_eq!(paused.frame_interval(), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn finished_alarms_move_on_to_the_next() {
+        let now = SystemTime::now();
+        let hour = Duration::from_secs(60 * 60);
+        let mut timers = Timers::default()
+            .with_timer(Timer::new(Mode::Countdown(Duration::from_secs(1))).with_running(true))
+            .with_alarms(vec![now + hour, now + Duration::from_secs(1)]);
+        timers.tick(Duration::from_secs(2));
+
+        let timer = timers.selected();
+        assert!(timer.is_running());
+        assert!(!timer.is_finished());
+        let Mode::Countdown(until) = timer.mode() else {
+            panic!("{:?}", timer.mode());
+        };
+        assert!(
+            until <= hour && until > hour - Duration::from_secs(5),
+            "{until:?}"
+        );
+        assert_eq!(timers.alarms, [now + hour]);
+    }
+
+    #[test]
+    fn countdown_snapshot() {
+        let mut timers = Timers::default()
+            .with_timer(
+                Timer::new(Mode::Countdown(Duration::from_secs(90)))
+                    .with_label(Some("tea".to_string())),
+            )
+            .with_digit_size(DigitSize::Sextant)
+            .with_precision(Precision::Seconds);
+        assert_eq!(
+            rows(&timers.render_to_buffer(Rect::new(0, 0, 40, 7))),
+            [
+                "            [countdown] tea           ⠋ ",
+                "                                        ",
+                "    🬻🬂🬸🬓🬻🬂🬸🬓 🬹  🬻🬂🬸🬓🬇█   🬹  🬍🬂🬺 🬻🬂🬸🬓    ",
+                "    █🬜🬨▌█🬜🬨▌ 🬭  █🬜🬨▌ █   🬭  🬭🬂🬺 █🬜🬨▌    ",
+                "    🬁🬂🬂 🬁🬂🬂  🬂  🬁🬂🬂 🬂🬂🬂  🬂  🬁🬂🬀 🬁🬂🬂     ",
+                "                                        ",
+                " Toggle <Space> Reset <R> Exit <Escape> ",
+            ]
+        );
+    }
+
+    #[test]
+    fn left_aligned_stopwatch_snapshot() {
+        let mut timers = Timers::default()
+            .with_timer(Timer::new(Mode::Stopwatch).with_elapsed(Duration::from_millis(61_500)))
+            .with_digit_size(DigitSize::Sextant)
+            .with_precision(Precision::Tenths)
+            .with_align(Align::Left);
+        assert_eq!(
+            rows(&timers.render_to_buffer(Rect::new(0, 0, 40, 7))),
+            [
+                "              [stopwatch]             ⠋ ",
+                "                                        ",
+                "🬻🬂🬸🬓🬻🬂🬸🬓 🬹  🬻🬂🬸🬓🬇█   🬹  🬻🬂🬸🬓🬇█      █🬰🬒 ",
+                "█🬜🬨▌█🬜🬨▌ 🬭  █🬜🬨▌ █   🬭  █🬜🬨▌ █   🬭  🬭 █ ",
+                "🬁🬂🬂 🬁🬂🬂  🬂  🬁🬂🬂 🬂🬂🬂  🬂  🬁🬂🬂 🬂🬂🬂  🬂  🬁🬂🬀 ",
+                "                                        ",
+                " Toggle <Space> Reset <R> Exit <Escape> ",
+            ]
+        );
+    }
+
+    #[test]
+    fn key_hints_that_do_not_fit_are_left_out() {
+        let hints = |width, segmented| Timers::instructions(width, segmented).to_string();
+        assert_eq!(
+            hints(80, false),
+            "Toggle <Space> Reset <R> Lap <L> Pause all <P> Invert <I> Mode <M> Exit <Escape>"
+        );
+        assert_eq!(
+            hints(80, true),
+            "Toggle <Space> Reset <R> Select <Up/Down> Lap <L> Pause all <P> Exit <Escape>"
+        );
+        assert_eq!(hints(20, false), "Toggle <Space> Reset <R> Exit <Escape>");
+        assert!(hints(200, true).contains("Collapse <C>"));
+        for width in 38..120 {
+            assert!(hints(width, true).len() <= usize::from(width), "{width}");
+        }
+    }
+
+    #[test]
+    fn snapshot_cells_take_the_theme_colors() {
+        let theme = Theme::preset("nord").unwrap();
+        let mut timers = Timers::default()
+            .with_theme(theme)
+            .with_digit_size(DigitSize::Sextant);
+        let buf = timers.render_to_buffer(Rect::new(0, 0, 60, 7));
+        for cell in &buf.content {
+            assert_eq!(cell.bg, theme.bg, "{cell:?}");
+            assert_eq!(cell.fg, theme.fg, "{cell:?}");
+        }
+    }
+
+    #[test]
+    fn laps_are_compared_with_their_planned_splits() {
+        let mut timers = Timers::default()
+            .with_timer(Timer::new(Mode::Stopwatch).with_running(true))
+            .with_precision(Precision::Seconds)
+            .with_splits(vec![Duration::from_secs(30), Duration::from_secs(60)]);
+        for seconds in [28, 35, 20] {
+            timers.tick(Duration::from_secs(seconds));
+            timers.apply(Action::Lap);
+        }
+
+        // Newest first, after the lap in progress, and the third lap had nothing planned
+        let lines = timers.lap_lines();
+        let differences: Vec<(String, Option<Color>)> = lines[1..]
+            .iter()
+            .map(|line| match &line.spans[..] {
+                [_, difference] => (difference.content.to_string(), difference.style.fg),
+                _ => (String::new(), None),
+            })
+            .collect();
+        assert_eq!(
+            differences,
+            [
+                (String::new(), None),
+                ("  +00:00:03".to_string(), Some(Color::Red)),
+                ("  -00:00:02".to_string(), Some(Color::Green)),
+            ]
+        );
+    }
+
+    #[test]
+    fn collapsing_draws_a_single_line() {
+        let mut timers = Timers::default()
+            .with_timer(Timer::new(Mode::Countdown(Duration::from_secs(90))).with_running(true))
+            .with_precision(Precision::Seconds)
+            .with_collapsed(true);
+        let collapsed = rows(&timers.render_to_buffer(Rect::new(0, 0, 50, 4)));
+        assert_eq!(
+            collapsed[0].trim_end(),
+            "countdown 00:01:30 running Expand <C>"
+        );
+        assert!(
+            collapsed[1..].iter().all(|row| row.trim().is_empty()),
+            "{collapsed:#?}"
+        );
+
+        timers.apply(Action::Collapse);
+        let expanded = rows(&timers.render_to_buffer(Rect::new(0, 0, 50, 4)));
+        assert!(
+            expanded[1..].iter().any(|row| !row.trim().is_empty()),
+            "{expanded:#?}"
+        );
+    }
+
+    #[test]
+    fn exported_laps_keep_their_milliseconds_when_they_are_hidden() {
+        let mut timers = Timers::default()
+            .with_timer(
+                Timer::new(Mode::Stopwatch)
+                    .with_label(Some("run, fast".to_string()))
+                    .with_running(true),
+            )
+            .with_precision(Precision::Seconds);
+        timers.tick(Duration::from_millis(12_345));
+        timers.apply(Action::Lap);
+        timers.tick(Duration::from_millis(1_001));
+        timers.apply(Action::Lap);
+
+        assert_eq!(timers.display_text(timers.selected()), "00:00:13");
+        assert_eq!(
+            timers.laps_csv(),
+            "timer,lap,kind,split,lap_time\n\
+             \"run, fast\",1,manual,00:00:12.345,00:00:12.345\n\
+             \"run, fast\",2,manual,00:00:13.346,00:00:01.001\n"
+        );
+    }
+
+    #[test]
+    fn slots_swap_the_timers_out_wholesale() {
+        let dir = std::env::temp_dir().join(format!("timers-slots-{}", std::process::id()));
+        let mut timers = Timers::default()
+            .with_timer(
+                Timer::new(Mode::Stopwatch)
+                    .with_label(Some("stashed".to_string()))
+                    .with_running(true),
+            )
+            .with_slots(dir.clone());
+        timers.tick(Duration::from_secs(90));
+        timers.apply(Action::Toggle);
+        timers.handle_key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE));
+        assert_eq!(timers.slot_note(), Some("save to slot 1-9"));
+        timers.handle_key(KeyEvent::new(KeyCode::Char('3'), KeyModifiers::NONE));
+        assert_eq!(timers.slot_note(), Some("saved slot 3"));
+        timers.handle_key(KeyEvent::new(KeyCode::Char('3'), KeyModifiers::NONE));
+        assert!(!timers.choosing_slot);
+
+        timers.handle_key(KeyEvent::new(KeyCode::Char('4'), KeyModifiers::ALT));
+        assert_eq!(timers.slot_note(), Some("slot 4 is empty"));
+        assert_eq!(timers.timers()[0].label(), Some("stashed"));
+
+        timers
+            .timers
+            .replace(vec![Timer::new(Mode::Countdown(Duration::from_secs(60)))]);
+        timers.apply(Action::LoadSlot(3));
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(timers.slot_note(), Some("loaded slot 3"));
+        let [timer] = timers.timers() else {
+            panic!("{} timers", timers.timers().len());
+        };
+        assert_eq!(timer.label(), Some("stashed"));
+        assert_eq!(timer.mode(), Mode::Stopwatch);
+        assert_eq!(timer.value(), Duration::from_secs(90));
+        assert!(!timer.is_running());
+    }
+
+    #[test]
+    fn handed_over_timers_are_set_up_as_they_were() {
+        let countdown = Timer::new(Mode::Countdown(Duration::from_secs(10)))
+            .with_tick_last(3)
+            .with_lead_in(Duration::from_secs(2))
+            .with_then_stopwatch(true)
+            .with_running(true);
+        let stopwatch = Timer::new(Mode::Stopwatch)
+            .with_elapsed(Duration::from_secs(5))
+            .with_reset_to_seed(true)
+            .with_auto_pause(Some(Duration::from_secs(100)))
+            .with_auto_lap(Some(Duration::from_secs(30)));
+        let mut timers = Timers::default().with_timers(vec![countdown, stopwatch]);
+        timers.tick(Duration::from_secs(1));
+
+        let json = serde_json::to_string(&timers.handoff()).unwrap();
+        let taken_over = Timers::default().with_handoff(serde_json::from_str(&json).unwrap());
+        for (before, after) in timers.timers().iter().zip(taken_over.timers()) {
+            assert_eq!(after.settings(), before.settings());
+            assert_eq!(after.mode(), before.mode());
+        }
+        let left = taken_over.timers()[0]
+            .lead_in_after(Duration::ZERO)
+            .unwrap();
+        assert!(left <= Duration::from_secs(1) && left > Duration::from_millis(900));
+
+        timers.tick(Duration::from_secs(14));
+        let json = serde_json::to_string(&timers.handoff()).unwrap();
+        let mut taken_over = Timers::default().with_handoff(serde_json::from_str(&json).unwrap());
+        let (countdown, overrun) = taken_over.timers()[0].overrun().unwrap();
+        assert_eq!(countdown, Duration::from_secs(10));
+        assert!(overrun >= Duration::from_secs(3));
+        taken_over.timers.all_mut()[0].reset();
+        assert_eq!(
+            taken_over.timers()[0].mode(),
+            Mode::Countdown(Duration::from_secs(10))
+        );
+    }
+
+    #[test]
+    fn a_key_during_the_grace_period_keeps_the_app_open() {
+        let countdown = || Timer::new(Mode::Countdown(Duration::from_secs(5))).with_running(true);
+        let mut immediate = Timers::default()
+            .with_timer(countdown())
+            .with_exit_on_finish(true);
+        immediate.tick(Duration::from_secs(5));
+        assert!(immediate.should_exit());
+
+        let mut timers = Timers::default()
+            .with_timer(countdown())
+            .with_exit_on_finish(true)
+            .with_grace(Duration::from_secs(60));
+        timers.tick(Duration::from_secs(5));
+        timers.check_grace();
+        assert!(!timers.should_exit());
+        let instructions = rows(&timers.render_to_buffer(Rect::new(0, 0, 60, 12)))[0].clone();
+        assert!(
+            instructions.contains("exiting in 60s, any key to stay"),
+            "{instructions}"
+        );
+
+        timers.handle_key(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE));
+        assert!(!timers.acknowledge_grace());
+        assert!(!timers.selected().is_running());
+        timers.check_grace();
+        assert!(!timers.should_exit());
+
+        let mut quitting = Timers::default()
+            .with_timer(countdown())
+            .with_exit_on_finish(true)
+            .with_grace(Duration::from_secs(60));
+        quitting.tick(Duration::from_secs(5));
+        quitting.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(quitting.should_exit());
+    }
+
+    #[test]
+    fn fullwidth_numerals_are_centered_by_their_width() {
+        assert_eq!(Numerals::Fullwidth.apply("01:59.5"), "０１:５９.５");
+
+        let mut timers = Timers::default()
+            .with_timer(Timer::new(Mode::Stopwatch))
+            .with_numerals(Numerals::Fullwidth);
+        let area = Rect::new(0, 0, 40, 5);
+        let buf = timers.render_to_buffer(area);
+        // Nine digits two columns wide and three single column separators take 21 columns
+        let row = (0..area.height)
+            .find(|&y| buf[(9, y)].symbol() == "０")
+            .unwrap();
+        assert_eq!(buf[(8, row)].symbol(), " ");
+        assert_eq!(buf[(28, row)].symbol(), "０");
+        assert_eq!(buf[(30, row)].symbol(), " ");
+        assert!(!rows(&buf).concat().contains("too small"));
+    }
+
+    #[test]
+    fn overruns_are_shown_in_red_and_summed_up_on_exit() {
+        let mut timers = Timers::default().with_timer(
+            Timer::new(Mode::Countdown(Duration::from_secs(90)))
+                .with_then_stopwatch(true)
+                .with_running(true),
+        );
+        let timer = timers.selected().clone();
+        assert_eq!(timers.digit_line(&timer, String::new()).style.fg, None);
+        timers.tick(Duration::from_secs(90));
+        timers.tick(Duration::from_millis(12_500));
+        let timer = timers.selected().clone();
+        let line = timers.digit_line(&timer, String::new());
+
+        assert_eq!(line.style.fg, Some(OVERRUN_COLOR));
+        assert_eq!(
+            timers.overrun_summary().as_deref(),
+            Some("countdown 00:01:30, over by 00:00:12.500")
+        );
+        let summary = &timers.summary().timers[0];
+        assert_eq!(summary.countdown, Some(Duration::from_secs(90)));
+        assert_eq!(summary.overrun, Some(Duration::from_millis(12_500)));
+    }
+
+    #[test]
+    fn the_pulse_fades_between_seconds_and_stands_still_when_paused() {
+        let theme = Theme {
+            fg: Color::Rgb(200, 200, 200),
+            bg: Color::Rgb(0, 0, 0),
+            alarm: Color::Red,
+        };
+        let mut timers = Timers::default()
+            .with_timer(Timer::new(Mode::Stopwatch).with_running(true))
+            .with_precision(Precision::Seconds)
+            .with_theme(theme)
+            .with_pulse(true);
+        let area = Rect::new(0, 0, 80, 20);
+        let dot = |timers: &mut Timers| {
+            let buf = timers.render_to_buffer(area);
+            let cell = buf
+                .content()
+                .iter()
+                .find(|cell| cell.symbol() == "●")
+                .expect("no pulse drawn")
+                .clone();
+            cell.fg
+        };
+
+        assert_eq!(dot(&mut timers), theme.fg);
+        timers.tick(Duration::from_millis(500));
+        assert_eq!(dot(&mut timers), theme.bg);
+        timers.tick(Duration::from_millis(250));
+        assert_eq!(dot(&mut timers), Color::Rgb(100, 100, 100));
+
+        timers.apply(Action::Toggle);
+        timers.tick(Duration::from_millis(250));
+        assert_eq!(dot(&mut timers), Color::Rgb(100, 100, 100));
+    }
+
+    #[test]
+    fn banners_stand_in_for_the_digits_as_a_countdown_starts_and_finishes() {
+        let mut timers = Timers::default()
+            .with_timer(
+                Timer::new(Mode::Countdown(Duration::from_secs(30)))
+                    .with_lead_in(Duration::from_secs(3))
+                    .with_running(true),
+            )
+            .with_banners(Banners {
+                go: "GO".to_string(),
+                go_for: Duration::ZERO,
+                ..Banners::default()
+            });
+        timers.tick(Duration::from_secs(3));
+        assert_eq!(
+            timers.banner.as_ref().map(|(text, ..)| text.as_str()),
+            Some("GO")
+        );
+        // Shown for no time at all, so the digits are straight back
+        assert_eq!(timers.active_banner(), None);
+
+        let area = Rect::new(0, 0, 80, 20);
+        timers.tick(Duration::from_secs(30));
+        assert_eq!(timers.active_banner(), Some("DONE"));
+        let done = rows(&timers.render_to_buffer(area));
+        timers.tick(Duration::from_secs(60));
+        assert_eq!(timers.active_banner(), Some("DONE"));
+
+        timers.apply(Action::Banners);
+        assert_eq!(timers.active_banner(), None);
+        assert_ne!(rows(&timers.render_to_buffer(area)), done);
+        timers.apply(Action::Reset);
+        timers.apply(Action::Toggle);
+        timers.tick(Duration::from_secs(33));
+        assert_eq!(timers.active_banner(), None);
+    }
+
+    #[test]
+    fn a_fixed_width_keeps_the_seconds_in_place_as_the_hours_shrink() {
+        let hundred_hours = Duration::from_secs(100 * 3600);
+        let area = Rect::new(0, 0, 120, 12);
+        // The column the rightmost drawn cell of the digits is in
+        let right_edge = |timers: &mut Timers| {
+            let buf = timers.render_to_buffer(area);
+            (area.left()..area.right())
+                .filter(|&x| (2..8).any(|y| buf[(x, y)].symbol() != " "))
+                .max()
+                .unwrap()
+        };
+        for (fixed_width, moves) in [(false, true), (true, false)] {
+            let mut timers = Timers::default()
+                .with_timer(Timer::new(Mode::Countdown(hundred_hours)).with_running(true))
+                .with_digit_size(DigitSize::Quadrant)
+                .with_fixed_width(fixed_width);
+            let before = right_edge(&mut timers);
+            timers.tick(Duration::from_secs(1));
+            assert_eq!(right_edge(&mut timers) != before, moves);
+        }
+    }
+
+    #[test]
+    fn workouts_move_through_their_phases_before_finishing() {
+        let phase = |name: &str, seconds, color| Phase {
+            name: name.to_string(),
+            duration: Duration::from_secs(seconds),
+            color,
+            sound: None,
+        };
+        let workout = Workout {
+            phases: vec![phase("work", 20, Some(Color::Red)), phase("rest", 10, None)],
+            repeat: 2,
+        };
+        let mut timers = Timers::default()
+            .with_timer(
+                Timer::new(Mode::Countdown(Duration::from_secs(20)))
+                    .with_label(Some("work".to_string()))
+                    .with_running(true),
+            )
+            .with_workout(workout)
+            .with_exit_on_finish(true);
+        let color = |timers: &Timers| {
+            let timer = timers.selected().clone();
+            timers.digit_line(&timer, String::new()).style.fg
+        };
+        assert_eq!(color(&timers), Some(Color::Red));
+
+        timers.tick(Duration::from_secs(20));
+        assert!(!timers.should_exit());
+        assert_eq!(timers.selected().label(), Some("rest"));
+        assert_eq!(timers.timer(), Duration::from_secs(10));
+        assert_eq!(color(&timers), None);
+        let title = rows(&timers.render_to_buffer(Rect::new(0, 0, 60, 12))).concat();
+        assert!(title.contains("[countdown] rest 2/4"), "{title}");
+
+        timers.tick(Duration::from_secs(10));
+        assert_eq!(timers.selected().label(), Some("work"));
+        assert_eq!(color(&timers), Some(Color::Red));
+        timers.tick(Duration::from_secs(20));
+        assert!(!timers.should_exit());
+        timers.tick(Duration::from_secs(10));
+        assert_eq!(timers.selected().label(), Some("rest"));
+        assert!(timers.should_exit());
+    }
+
+    #[test]
+    fn workouts_announce_their_first_phase_once_started_and_reset_back_to_it() {
+        let phase = |name: &str, seconds| Phase {
+            name: name.to_string(),
+            duration: Duration::from_secs(seconds),
+            color: None,
+            sound: None,
+        };
+        let mut timers = Timers::default()
+            .with_timer(
+                Timer::new(Mode::Countdown(Duration::from_secs(20)))
+                    .with_label(Some("work".to_string())),
+            )
+            .with_workout(Workout {
+                phases: vec![phase("work", 20), phase("rest", 10)],
+                repeat: 3,
+            });
+
+        timers.tick(Duration::from_secs(1));
+        assert!(!timers.phase_announced);
+        timers.apply(Action::Toggle);
+        timers.tick(Duration::from_secs(1));
+        assert!(timers.phase_announced);
+
+        timers.tick(Duration::from_secs(19));
+        timers.tick(Duration::from_secs(4));
+        assert_eq!(timers.selected().label(), Some("rest"));
+        assert_eq!(timers.phase, 1);
+
+        timers.apply(Action::Reset);
+        assert_eq!(timers.phase, 0);
+        assert_eq!(timers.selected().label(), Some("work"));
+        assert_eq!(timers.timer(), Duration::from_secs(20));
+        assert!(timers.selected().is_running());
+        assert!(!timers.phase_announced);
+        timers.tick(Duration::from_secs(1));
+        assert!(timers.phase_announced);
+    }
+
+    #[test]
+    fn workouts_count_every_round_without_copying_it() {
+        let workout = Workout {
+            phases: vec![
+                Phase {
+                    name: "work".to_string(),
+                    duration: Duration::from_secs(20),
+                    color: None,
+                    sound: None,
+                };
+                2
+            ],
+            repeat: u32::MAX,
+        };
+        assert_eq!(workout.len(), 2 * u32::MAX as usize);
+        assert_eq!(workout.phase(workout.len() - 1), workout.phases.get(1));
+        assert_eq!(workout.phase(workout.len()), None);
+    }
+}
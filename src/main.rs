@@ -1,170 +1,1158 @@
-use clap::Parser;
-use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use chrono::{DateTime, Days, Local, NaiveDateTime, NaiveTime};
+use clap::{error::ErrorKind, CommandFactory, Parser, ValueEnum};
 use ratatui::{
-    buffer::Buffer,
-    layout::{Constraint, Flex, Layout, Rect},
-    style::{Color, Stylize},
-    text::Line,
-    widgets::{Block, StatefulWidget, Widget},
-    Frame,
+    crossterm::{
+        queue,
+        style::{Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    },
+    style::{Color, Modifier},
+};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    env,
+    ffi::OsString,
+    io::{stdout, IsTerminal, Result, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+use timers::{
+    code::{Setup, SetupSegment},
+    completion::CompletionAction,
+    config::{self, Config, Sounds, ThemeConfig, WorkoutConfig},
+    control::ControlPipe,
+    daemon,
+    duration::{parse_duration, TimeFormat},
+    serve::StatusServer,
+    session::{self, Session},
+    tui::{self, Screen},
+    Action, AlarmEffect, Align, Banners, DigitSize, Mode, Numerals, Precision, Rotation, Theme,
+    Timer, Timers, TitlePosition, Workout,
 };
-use std::{io::Result, time::Duration};
-use tui::Tui;
-use tui_widgets::big_text::BigText;
-
-mod tui;
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 struct Cli {
+    /// Start from a built in theme or one from the config file; `--fg`, `--bg` and
+    /// `--alarm-color` override its colors
+    #[arg(short, long, value_name = "NAME")]
+    theme: Option<String>,
+
+    /// Print the built in and configured themes and exit
+    #[arg(long)]
+    list_themes: bool,
+
+    /// Read settings such as custom themes from this file instead of
+    /// `~/.config/timers/config.toml`
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Print the settings the flags and config file resolve to as TOML and exit without
+    /// starting the timer
+    #[arg(long, visible_alias = "print-config")]
+    dry_run: bool,
+
+    /// Print a code that launches this timer, with its durations, label and colors, for
+    /// `--code` and exit
+    #[arg(long, conflicts_with_all = ["since", "alarm"])]
+    export_code: bool,
+
+    /// Launch the timer a code from `--export-code` describes; colors given alongside it win
+    #[arg(long, value_parser = Setup::decode,
+          conflicts_with_all = ["countdown", "elapsed", "since", "alarm", "clock", "segment", "label"])]
+    code: Option<Setup>,
+
+    /// Color of the digits; without `--bg` or `--theme` the background is picked to contrast
     #[arg(short, long)]
     fg: Option<Color>,
 
+    /// Background color; without `--fg` or `--theme` the digits are picked to contrast
     #[arg(short, long)]
     bg: Option<Color>,
+
+    /// Color used by the alarm effect when a countdown finishes
+    #[arg(long)]
+    alarm_color: Option<Color>,
+
+    /// How the display reacts when a countdown finishes
+    #[arg(long, value_enum, default_value_t)]
+    alarm_effect: AlarmEffect,
+
+    #[arg(short, long)]
+    label: Option<String>,
+
+    #[arg(long, value_enum, default_value_t)]
+    title_position: TitlePosition,
+
+    /// How many terminal cells each pixel of the digits takes up
+    #[arg(long, value_enum, default_value_t)]
+    pixel_size: DigitSize,
+
+    /// Start as a single line with the time and whether it is running; `c` switches between
+    /// that and the big digits
+    #[arg(long)]
+    collapsed: bool,
+
+    /// Where the digits sit across the window
+    #[arg(long, value_enum, default_value_t)]
+    align: Align,
+
+    /// Pad the time to the widest it is expected to get, such as the full countdown, so the
+    /// digits never shift as it changes
+    #[arg(long)]
+    fixed_width: bool,
+
+    /// The glyphs to write the digits with; `fullwidth` writes the time as wide text instead of
+    /// big digits
+    #[arg(long, value_enum, default_value_t)]
+    numerals: Numerals,
+
+    /// Turn the big digits upside down with `180`, for someone sitting opposite
+    #[arg(long, value_enum, default_value_t)]
+    rotate: Rotation,
+
+    /// Draw the big digits reversed left to right, as if seen in a mirror
+    #[arg(long)]
+    mirror: bool,
+
+    /// Draw the digits in bold, as does `bold = true` in the config file
+    #[arg(long)]
+    bold: bool,
+
+    /// Draw the digits in italics, as does `italic = true` in the config file
+    #[arg(long)]
+    italic: bool,
+
+    /// Focus mode: bright hours and minutes, dimmed seconds, no fraction
+    #[arg(long)]
+    focus: bool,
+
+    /// How the sub-second part of the time is shown; `bar` draws a bar under the seconds that
+    /// fills up once a second
+    #[arg(long, value_enum, default_value_t, conflicts_with_all = ["tenths", "no_ms"])]
+    subsecond: Precision,
+
+    /// Show tenths of a second instead of milliseconds, like `--subsecond tenths`
+    #[arg(long, conflicts_with = "no_ms")]
+    tenths: bool,
+
+    /// Hide the sub-second part of the time, like `--subsecond none`
+    #[arg(long)]
+    no_ms: bool,
+
+    /// Show only hours and minutes, drawing once a minute, for timers watched from across the
+    /// room
+    #[arg(long, conflicts_with_all = ["subsecond", "tenths", "no_ms", "round"])]
+    hm: bool,
+
+    /// Round the seconds to the nearest one instead of down when the fraction is hidden
+    #[arg(long)]
+    round: bool,
+
+    /// Split whole days off the hours once the time reaches a day, as in `1d 02:03:04`
+    #[arg(long)]
+    days: bool,
+
+    /// How many times a second the display is redrawn [default: 60, or 10 without the TUI]
+    #[arg(long, value_parser = parse_fps)]
+    fps: Option<f64>,
+
+    /// Count down from the given duration (e.g. `90s`, `25m`, `1h30m`, `1:30`, `in 5 minutes`)
+    #[arg(short, long, value_parser = parse_duration)]
+    countdown: Option<Duration>,
+
+    /// Count up from a local time in the past, e.g. `2024-05-01T09:00:00`
+    #[arg(long, value_name = "YYYY-MM-DDTHH:MM:SS", value_parser = parse_timestamp,
+          conflicts_with_all = ["countdown", "elapsed"])]
+    since: Option<SystemTime>,
+
+    /// Count down to a time of day such as `12:00` or `15:30:15`, tomorrow if it has passed
+    /// today; may be repeated, counting down to each in turn
+    #[arg(long, value_name = "HH:MM[:SS]", value_parser = parse_alarm,
+          conflicts_with_all = ["countdown", "since", "elapsed", "segment", "clock", "paused"])]
+    alarm: Vec<NaiveTime>,
+
+    /// Show the local time of day instead of a timer
+    #[arg(long, conflicts_with_all = ["countdown", "since", "elapsed", "segment"])]
+    clock: bool,
+
+    /// Draw a bar under the clock that sweeps across once a minute
+    #[arg(long, requires = "clock")]
+    sweep: bool,
+
+    /// Show the elapsed time dimly under the countdown's remaining time
+    #[arg(long, requires = "countdown")]
+    watermark: bool,
+
+    /// Run a workout from the config file, counting down each of its phases in turn
+    #[arg(long, value_name = "NAME",
+          conflicts_with_all = ["countdown", "since", "elapsed", "alarm", "clock", "segment",
+                                "label", "code", "then_stopwatch"])]
+    workout: Option<String>,
+
+    /// When the countdown finishes, keep counting up in red to show how long it has run over
+    #[arg(long, conflicts_with_all = ["since", "elapsed", "clock", "alarm", "exit_on_finish"])]
+    then_stopwatch: bool,
+
+    /// Start the stopwatch from this much elapsed time instead of zero
+    #[arg(short, long, value_parser = parse_duration, conflicts_with = "countdown")]
+    elapsed: Option<Duration>,
+
+    /// Count this down first, in a different color, before the timer itself starts
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration,
+          conflicts_with_all = ["since", "clock"])]
+    lead_in: Option<Duration>,
+
+    /// Pause the stopwatch with a bell once it reaches this much elapsed time
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    auto_pause: Option<Duration>,
+
+    /// Record a lap automatically every time this much more time has passed
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    auto_lap: Option<Duration>,
+
+    /// A duration the stopwatch is working towards, shown as progress with `--percent`
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration,
+          conflicts_with = "countdown")]
+    target: Option<Duration>,
+
+    /// Show how far through the countdown, or towards `--target`, it is in the title
+    #[arg(long)]
+    percent: bool,
+
+    /// Planned times for each lap to be recorded at, e.g. `0:30,1:00,1:30`; each lap shows how
+    /// far ahead or behind its planned time it was
+    #[arg(long, value_name = "DURATION,...", value_delimiter = ',', value_parser = parse_duration)]
+    splits: Vec<Duration>,
+
+    /// Draw a bar chart of the recent lap times beside the lap list
+    #[arg(long)]
+    lap_chart: bool,
+
+    /// Draw a dot beside the seconds that brightens and fades once a second while the timer
+    /// runs, for a sign of life without sub-second digits
+    #[arg(long)]
+    pulse: bool,
+
+    /// Draw a pie in the corner that fills up with the countdown, or towards `--target`
+    #[arg(long)]
+    pie: bool,
+
+    /// Reset the stopwatch to the `--elapsed` value instead of zero
+    #[arg(long, requires = "elapsed")]
+    reset_to_seed: bool,
+
+    /// Stack several independent timers, each given as `LABEL` for a stopwatch or
+    /// `LABEL=DURATION` for a countdown; may be repeated
+    #[arg(long, value_name = "LABEL[=DURATION]", value_parser = parse_segment,
+          conflicts_with_all = ["countdown", "since", "elapsed", "quiet", "label"])]
+    segment: Vec<Timer>,
+
+    /// Ring the terminal bell on each of the countdown's last N seconds
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    tick_last: u32,
+
+    /// Show a big banner in place of the digits as the lead-in runs out and when the countdown
+    /// finishes, until a key is pressed; `B` turns them off and on
+    #[arg(long)]
+    banners: bool,
+
+    /// The banner flashed as the lead-in runs out
+    #[arg(long, value_name = "TEXT", default_value = "GO!", requires = "banners")]
+    go_text: String,
+
+    /// The banner shown when the countdown finishes
+    #[arg(
+        long,
+        value_name = "TEXT",
+        default_value = "DONE",
+        requires = "banners"
+    )]
+    done_text: String,
+
+    /// How long the banner is flashed for as the lead-in runs out
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, default_value = "1s",
+          requires = "banners")]
+    go_for: Duration,
+
+    /// Flash the background and show a note when the countdown passes halfway
+    #[arg(long)]
+    halfway: bool,
+
+    /// Ring the terminal bell as well when the countdown passes halfway
+    #[arg(long, requires = "halfway")]
+    halfway_bell: bool,
+
+    /// Exit once a countdown finishes, after the grace period
+    #[arg(long)]
+    exit_on_finish: bool,
+
+    /// How long `--exit-on-finish` keeps alarming before exiting; pressing any key in the
+    /// meantime keeps the app open, and `0s` exits straight away
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, default_value = "3s")]
+    grace: Duration,
+
+    /// Ring the terminal bell when a countdown finishes
+    #[arg(long)]
+    bell: bool,
+
+    /// Show a desktop notification when a countdown finishes
+    #[arg(long)]
+    notify: bool,
+
+    /// Shell command to run when a countdown finishes
+    #[arg(long, value_name = "COMMAND")]
+    on_finish: Option<String>,
+
+    /// Sound file to play when a countdown finishes
+    #[arg(long, value_name = "FILE")]
+    sound: Option<PathBuf>,
+
+    /// Print the countdown on a single line in the plain terminal instead of the TUI
+    #[arg(short, long, requires = "countdown", conflicts_with = "paused")]
+    quiet: bool,
+
+    /// Start the timer running immediately
+    #[arg(short, long, conflicts_with = "paused")]
+    start: bool,
+
+    /// Start the timer paused (the default)
+    #[arg(short, long)]
+    paused: bool,
+
+    /// Resume the stopwatch, its laps and any inverted colors from the last session, and save
+    /// them on exit
+    #[arg(long)]
+    persist: bool,
+
+    /// Discard any saved session before starting
+    #[arg(long)]
+    fresh: bool,
+
+    /// Answer TCP connections on this address with the timers' state as JSON, e.g.
+    /// `127.0.0.1:7878`
+    #[arg(long, value_name = "ADDR")]
+    serve: Option<String>,
+
+    /// Create a named pipe here and read commands from it, one per line: `toggle`, `reset`,
+    /// `lap`, `set DURATION`, `next`, `previous`, `pause-all`, `invert`, `mode`, `collapse`,
+    /// `banners`, `save SLOT`, `load SLOT`, `detach` or `quit`
+    #[arg(long, value_name = "PATH")]
+    control_pipe: Option<PathBuf>,
+
+    /// Keep the timer running in a background process instead of the terminal; pick it up again
+    /// with `--attach`
+    #[arg(long, conflicts_with_all = ["quiet", "attach", "control_pipe"])]
+    daemon: bool,
+
+    /// Take over the timers from the one running in the background, carrying on where they
+    /// are; press `d` to send them back
+    #[arg(long)]
+    attach: bool,
+
+    /// Run as the background process started by `--daemon` or by detaching
+    #[arg(long, hide = true, conflicts_with_all = ["quiet", "control_pipe"])]
+    daemon_child: bool,
+
+    /// Write every recorded lap to this file as CSV on exit, to the millisecond even when
+    /// they aren't shown
+    #[arg(long, value_name = "FILE")]
+    export_laps: Option<PathBuf>,
+
+    /// Print the final state of every timer as JSON on exit; the TUI is drawn on stderr, so
+    /// stdout can be redirected to keep it
+    #[arg(long)]
+    json: bool,
+
+    /// Print the final time on exit using this template, e.g. `%H:%M:%S`; supports `%H`, `%M`,
+    /// `%S`, `%L` (milliseconds), `%T` (total seconds), `%l` (total milliseconds) and `%%`
+    #[arg(long, value_name = "TEMPLATE", value_parser = TimeFormat::parse,
+          conflicts_with = "json")]
+    format: Option<TimeFormat>,
+
+    /// Show how long the timer has spent paused in the title
+    #[arg(long)]
+    show_paused: bool,
+
+    /// Exit after this long with the timer paused and no key presses
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    idle_exit: Option<Duration>,
+
+    /// Draw in a few rows of the normal screen instead of the alternate screen, so the last
+    /// frame stays in the scrollback on exit
+    #[arg(long)]
+    no_altscreen: bool,
+
+    /// Show the time and whether it is running in the terminal window's title, putting the old
+    /// title back on exit
+    #[arg(long)]
+    set_title: bool,
+
+    /// Keep the system from sleeping while the timer is running
+    #[arg(long)]
+    keep_awake: bool,
+}
+
+fn parse_fps(input: &str) -> std::result::Result<f64, String> {
+    match input.parse::<f64>() {
+        Ok(fps) if fps > 0.0 && fps.is_finite() => Ok(fps),
+        _ => Err(format!("`{input}` is not a positive number")),
+    }
 }
 
-#[derive(Clone, Copy)]
-pub struct Theme {
-    fg: Color,
-    bg: Color,
+/// Look up a theme by name, exiting with a usage error listing the choices if there is none
+fn find_theme(config: &Config, name: &str) -> Theme {
+    config.theme(name).unwrap_or_else(|| {
+        let names: Vec<String> = config
+            .all_themes()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        Cli::command()
+            .error(
+                ErrorKind::InvalidValue,
+                format!(
+                    "unknown theme `{name}`, expected one of: {}",
+                    names.join(", ")
+                ),
+            )
+            .exit()
+    })
 }
 
-impl Default for Theme {
-    fn default() -> Self {
-        Theme {
-            fg: Color::White,
-            bg: Color::Black,
+/// Look up a workout from the config file by name, exiting with the ones there are if it isn't
+/// there
+fn find_workout(config: &Config, name: &str) -> Workout {
+    let workout = config.workouts.get(name).map(WorkoutConfig::workout);
+    match workout {
+        Some(Ok(workout)) => workout,
+        // Every workout was checked as the config file was read
+        Some(Err(error)) => Cli::command()
+            .error(ErrorKind::InvalidValue, format!("workout `{name}` {error}"))
+            .exit(),
+        None => {
+            let names: Vec<&str> = config.workouts.keys().map(String::as_str).collect();
+            let expected = if names.is_empty() {
+                "the config file has none".to_string()
+            } else {
+                format!("expected one of: {}", names.join(", "))
+            };
+            Cli::command()
+                .error(
+                    ErrorKind::InvalidValue,
+                    format!("unknown workout `{name}`, {expected}"),
+                )
+                .exit()
         }
     }
 }
 
-pub struct TimersState {
-    theme: Theme,
+/// Print every theme, with a sample of its colors when stdout is a terminal
+fn list_themes(config: &Config) -> Result<()> {
+    let mut stdout = stdout();
+    let colored = stdout.is_terminal();
+    for (name, theme) in config.all_themes() {
+        if colored {
+            queue!(
+                stdout,
+                SetForegroundColor(theme.fg.into()),
+                SetBackgroundColor(theme.bg.into()),
+                Print(" 12:34 "),
+                SetBackgroundColor(theme.alarm.into()),
+                Print("  "),
+                ResetColor,
+                Print(format!(" {name}\n")),
+            )?;
+        } else {
+            writeln!(stdout, "{name}")?;
+        }
+    }
+    stdout.flush()
 }
 
-#[derive(Default)]
-pub struct Timers {
-    exit: bool,
+/// The settings a run would use, as printed by `--dry-run`
+#[derive(Serialize)]
+struct Resolved<'a> {
+    config_file: Option<PathBuf>,
+    timer: ResolvedTimer,
+    display: ResolvedDisplay,
+    run: ResolvedRun,
+    on_finish: Vec<String>,
+    theme: BTreeMap<&'static str, String>,
+    sounds: &'a Sounds,
+    themes: &'a BTreeMap<String, ThemeConfig>,
+    workouts: &'a BTreeMap<String, WorkoutConfig>,
+    keys: BTreeMap<&'static str, &'static str>,
+}
+
+#[derive(Serialize)]
+struct ResolvedTimer {
+    mode: &'static str,
+    label: Option<String>,
+    countdown: Option<String>,
+    elapsed: Option<String>,
+    alarms: Vec<String>,
     running: bool,
-    timer: Duration,
-    theme: Theme,
+    segments: Vec<String>,
+    workout: Option<String>,
+    lead_in: Option<String>,
+    auto_pause: Option<String>,
+    auto_lap: Option<String>,
+    target: Option<String>,
+    splits: Vec<String>,
+    tick_last: u32,
+    then_stopwatch: bool,
+    reset_to_seed: bool,
 }
 
-impl Timers {
-    pub fn with_theme(mut self, theme: Theme) -> Self {
-        self.theme = theme;
-        self
+#[derive(Serialize)]
+struct ResolvedDisplay {
+    screen: &'static str,
+    collapsed: bool,
+    pixel_size: String,
+    align: String,
+    fixed_width: bool,
+    numerals: String,
+    subsecond: String,
+    round: bool,
+    days: bool,
+    bold: bool,
+    italic: bool,
+    focus: bool,
+    title_position: String,
+    alarm_effect: String,
+    rotate: String,
+    mirror: bool,
+    sweep: bool,
+    watermark: bool,
+    percent: bool,
+    pie: bool,
+    pulse: bool,
+    lap_chart: bool,
+    halfway: bool,
+    halfway_bell: bool,
+    show_paused: bool,
+    set_title: bool,
+    fps: f64,
+    /// Left out unless `--banners` is given
+    banners: Option<ResolvedBanners>,
+}
+
+/// How the run starts, ends and talks to the world outside the terminal
+#[derive(Serialize)]
+struct ResolvedRun {
+    exit_on_finish: bool,
+    grace: String,
+    idle_exit: Option<String>,
+    keep_awake: bool,
+    persist: bool,
+    fresh: bool,
+    attach: bool,
+    serve: Option<String>,
+    control_pipe: Option<PathBuf>,
+    export_laps: Option<PathBuf>,
+    json: bool,
+    format: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ResolvedBanners {
+    go: String,
+    done: String,
+    go_for: String,
+}
+
+/// The name a value is given on the command line
+fn value_name<T: ValueEnum>(value: &T) -> String {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+/// What the flags, the config file and the defaults add up to as TOML, for `--dry-run`
+fn resolved_config(
+    cli: &Cli,
+    config: &Config,
+    theme: Theme,
+    mode: Mode,
+    precision: Precision,
+    running: bool,
+) -> Result<String> {
+    let duration = |duration: Duration| format!("{duration:?}");
+    let config_file = cli
+        .config
+        .clone()
+        .or_else(|| config::default_path().filter(|path| path.exists()));
+    let mut on_finish = Vec::new();
+    if cli.bell {
+        on_finish.push("bell".to_string());
+    }
+    if cli.notify {
+        on_finish.push("notify".to_string());
+    }
+    if let Some(command) = &cli.on_finish {
+        on_finish.push(format!("command {command}"));
     }
+    if let Some(path) = &cli.sound {
+        on_finish.push(format!("sound {}", path.display()));
+    }
+    let resolved = Resolved {
+        config_file,
+        timer: ResolvedTimer {
+            mode: mode.name(),
+            label: cli.label.clone(),
+            countdown: cli.countdown.map(duration),
+            elapsed: cli.elapsed.map(duration),
+            alarms: cli.alarm.iter().map(ToString::to_string).collect(),
+            running,
+            segments: cli
+                .segment
+                .iter()
+                .map(|segment| segment.label().unwrap_or_default().to_string())
+                .collect(),
+            workout: cli.workout.clone(),
+            lead_in: cli.lead_in.map(duration),
+            auto_pause: cli.auto_pause.map(duration),
+            auto_lap: cli.auto_lap.map(duration),
+            target: cli.target.map(duration),
+            splits: cli.splits.iter().copied().map(duration).collect(),
+            tick_last: cli.tick_last,
+            then_stopwatch: cli.then_stopwatch,
+            reset_to_seed: cli.reset_to_seed,
+        },
+        display: ResolvedDisplay {
+            screen: if cli.quiet {
+                "quiet"
+            } else if cli.no_altscreen {
+                "inline"
+            } else {
+                "alternate"
+            },
+            collapsed: cli.collapsed,
+            pixel_size: value_name(&cli.pixel_size),
+            align: value_name(&cli.align),
+            fixed_width: cli.fixed_width,
+            numerals: value_name(&cli.numerals),
+            subsecond: match precision {
+                Precision::Minutes => "hm".to_string(),
+                precision => value_name(&precision),
+            },
+            round: cli.round,
+            days: cli.days,
+            bold: cli.bold || config.bold,
+            italic: cli.italic || config.italic,
+            focus: cli.focus,
+            title_position: value_name(&cli.title_position),
+            alarm_effect: value_name(&cli.alarm_effect),
+            rotate: value_name(&cli.rotate),
+            mirror: cli.mirror,
+            sweep: cli.sweep,
+            watermark: cli.watermark,
+            percent: cli.percent,
+            pie: cli.pie,
+            pulse: cli.pulse,
+            lap_chart: cli.lap_chart,
+            halfway: cli.halfway,
+            halfway_bell: cli.halfway_bell,
+            show_paused: cli.show_paused,
+            set_title: cli.set_title,
+            banners: cli.banners.then(|| ResolvedBanners {
+                go: cli.go_text.clone(),
+                done: cli.done_text.clone(),
+                go_for: duration(cli.go_for),
+            }),
+            fps: cli.fps.unwrap_or(if cli.quiet { 10.0 } else { 60.0 }),
+        },
+        run: ResolvedRun {
+            exit_on_finish: cli.exit_on_finish,
+            grace: duration(cli.grace),
+            idle_exit: cli.idle_exit.map(duration),
+            keep_awake: cli.keep_awake,
+            persist: cli.persist,
+            fresh: cli.fresh,
+            attach: cli.attach,
+            serve: cli.serve.clone(),
+            control_pipe: cli.control_pipe.clone(),
+            export_laps: cli.export_laps.clone(),
+            json: cli.json,
+            format: cli
+                .format
+                .as_ref()
+                .map(|format| format.template().to_string()),
+        },
+        on_finish,
+        theme: BTreeMap::from([
+            ("fg", theme.fg.to_string()),
+            ("bg", theme.bg.to_string()),
+            ("alarm", theme.alarm.to_string()),
+        ]),
+        sounds: &config.sounds,
+        themes: &config.themes,
+        workouts: &config.workouts,
+        keys: Action::KEY_BINDINGS.into_iter().collect(),
+    };
+    toml::to_string(&resolved)
+        .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))
+}
 
-    pub fn run(&mut self, terminal: &mut Tui) -> Result<()> {
-        while !self.exit {
-            terminal.draw(|frame| self.render_frame(frame))?;
-            self.handle_events()?;
-        }
-        Ok(())
-    }
-
-    fn render_frame(&mut self, frame: &mut Frame) {
-        let state = &mut TimersState { theme: self.theme };
-
-        frame.render_stateful_widget(self, frame.area(), state);
-    }
-
-    fn handle_events(&mut self) -> Result<()> {
-        let frame_rate = Duration::from_secs_f64(1.0 / 60.0);
-        if event::poll(frame_rate)? {
-            match event::read()? {
-                Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-                    match (key_event.code, key_event.modifiers) {
-                        (KeyCode::Esc, KeyModifiers::NONE) => {
-                            self.exit = true;
-                        }
-                        (KeyCode::Char(' '), KeyModifiers::NONE) => {
-                            self.running = !self.running;
-                        }
-                        (KeyCode::Char('r'), KeyModifiers::NONE) => {
-                            self.timer = Duration::new(0, 0);
-                        }
-                        _ => (),
-                    }
-                }
-                _ => {}
-            }
-        }
-        if self.running {
-            self.timer += frame_rate;
-        }
-        Ok(())
+/// Parse a `LABEL` or `LABEL=DURATION` segment into a stopwatch or countdown
+fn parse_segment(input: &str) -> std::result::Result<Timer, String> {
+    let (label, mode) = match input.split_once('=') {
+        Some((label, duration)) => (label, Mode::Countdown(parse_duration(duration)?)),
+        None => (input, Mode::Stopwatch),
+    };
+    Ok(Timer::new(mode).with_label(Some(label.to_string())))
+}
+
+/// Fill in the flags a code from `--code` stands for, leaving any colors that were given
+fn apply_setup(cli: &mut Cli, setup: Setup) {
+    cli.countdown = setup.countdown;
+    cli.elapsed = setup.elapsed;
+    cli.clock = setup.clock;
+    cli.label = setup.label;
+    cli.segment = setup
+        .segments
+        .into_iter()
+        .map(|segment| {
+            let mode = segment.countdown.map_or(Mode::Stopwatch, Mode::Countdown);
+            Timer::new(mode).with_label(Some(segment.label))
+        })
+        .collect();
+    let colors_given = cli.theme.is_some() || cli.fg.is_some() || cli.bg.is_some();
+    if let (Some(theme), false) = (
+        setup.theme.and_then(|theme| theme.theme().ok()),
+        colors_given,
+    ) {
+        cli.fg = Some(theme.fg);
+        cli.bg = Some(theme.bg);
+        cli.alarm_color = cli.alarm_color.or(Some(theme.alarm));
     }
 }
 
-impl StatefulWidget for &mut Timers {
-    type State = TimersState;
+/// The part of the setup `--export-code` shares
+fn export_setup(cli: &Cli, theme: Theme) -> Setup {
+    let segments = cli
+        .segment
+        .iter()
+        .map(|segment| SetupSegment {
+            label: segment.label().unwrap_or_default().to_string(),
+            countdown: match segment.mode() {
+                Mode::Countdown(duration) => Some(duration),
+                _ => None,
+            },
+        })
+        .collect();
+    Setup {
+        countdown: cli.countdown,
+        elapsed: cli.elapsed,
+        clock: cli.clock,
+        label: cli.label.clone(),
+        segments,
+        theme: Some(ThemeConfig {
+            fg: theme.fg.to_string(),
+            bg: theme.bg.to_string(),
+            alarm: Some(theme.alarm.to_string()),
+        }),
+    }
+}
 
-    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-        let instructions = Line::from(vec![
-            "Toggle ".into(),
-            "<Space>".bold(),
-            " Reset ".into(),
-            "<R>".bold(),
-            " Exit ".into(),
-            "<Escape>".bold(),
-        ]);
+/// Parse a local date and time into the moment it refers to
+fn parse_timestamp(input: &str) -> std::result::Result<SystemTime, String> {
+    let naive = NaiveDateTime::parse_from_str(input, "%Y-%m-%dT%H:%M:%S")
+        .map_err(|error| format!("invalid timestamp `{input}`: {error}"))?;
+    let local = naive
+        .and_local_timezone(Local)
+        .earliest()
+        .ok_or_else(|| format!("`{input}` does not exist in the local time zone"))?;
+    Ok(local.into())
+}
 
-        let block = Block::new()
-            .bg(state.theme.bg)
-            .fg(state.theme.fg)
-            .title_bottom(instructions.centered());
-
-        let hours = self.timer.as_secs() / 60 / 60;
-        let hours_string = format!("{hours:02}");
-        let minutes = self.timer.as_secs() / 60 % 60;
-        let minutes_string = format!("{minutes:02}");
-        let seconds = self.timer.as_secs() % 60;
-        let seconds_string = format!("{seconds:02}");
-        let milliseconds = self.timer.subsec_millis();
-        let milliseconds_string = format!("{milliseconds:03}");
-
-        let timer_text = BigText::builder()
-            .pixel_size(tui_widgets::big_text::PixelSize::Quadrant)
-            .lines(vec![Line::from(vec![
-                hours_string.into(),
-                ":".into(),
-                minutes_string.into(),
-                ":".into(),
-                seconds_string.into(),
-                ".".into(),
-                milliseconds_string.into(),
-            ])])
-            .centered()
-            .build();
-
-        timer_text.render(block.inner(center_vertical(area, 6)), buf);
-        block.render(area, buf);
+/// Run this program again in the background with the same arguments, as a `--daemon-child`
+/// that also picks up a handoff when `attach` is set
+fn spawn_daemon(attach: bool) -> Result<()> {
+    let mut args: Vec<OsString> = env::args_os()
+        .skip(1)
+        .filter(|arg| arg != "--daemon" && arg != "--attach")
+        .collect();
+    args.push("--daemon-child".into());
+    if attach {
+        args.push("--attach".into());
     }
+    daemon::spawn(args)
+}
+
+/// The paths a background timer keeps its files at, or an error if there is nowhere to keep them
+fn daemon_paths() -> Result<(PathBuf, PathBuf)> {
+    daemon::control_path()
+        .zip(daemon::state_path())
+        .ok_or_else(|| std::io::Error::other("no home directory to keep the background timer in"))
+}
+
+/// Parse a time of day, with or without seconds
+fn parse_alarm(input: &str) -> std::result::Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(input, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(input, "%H:%M"))
+        .map_err(|_| format!("`{input}` is not a time of day like `12:00` or `15:30:15`"))
+}
+
+/// The next moment the local clock shows `time`, today or, once that has passed, tomorrow
+fn next_occurrence(time: NaiveTime, now: DateTime<Local>) -> SystemTime {
+    let today = now.date_naive();
+    [today, today + Days::new(1), today + Days::new(2)]
+        .into_iter()
+        .filter_map(|day| day.and_time(time).and_local_timezone(Local).earliest())
+        .find(|alarm| *alarm > now)
+        .map_or(now.into(), Into::into)
 }
 
 fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+    if let Some(setup) = cli.code.take() {
+        apply_setup(&mut cli, setup);
+    }
 
-    let default_theme = Theme::default();
+    if cli.daemon {
+        let (control, _) = daemon_paths()?;
+        if daemon::is_running(&control) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                "a timer is already running in the background; use `--attach` to pick it up",
+            ));
+        }
+        spawn_daemon(false)?;
+        println!("Timer running in the background; pick it up with `timers --attach`");
+        return Ok(());
+    }
 
+    // A config file that was asked for by name has to exist
+    let config = match (&cli.config, config::default_path()) {
+        (Some(path), _) => Config::load(path)?.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("{}: no such file", path.display()),
+            )
+        })?,
+        (None, Some(path)) => Config::load(&path)?.unwrap_or_default(),
+        (None, None) => Config::default(),
+    };
+
+    if cli.list_themes {
+        return list_themes(&config);
+    }
+
+    // The first phase is set up like any other countdown, and the rest follow it
+    let workout = cli
+        .workout
+        .as_deref()
+        .map(|name| find_workout(&config, name));
+    if let Some(first) = workout.as_ref().and_then(|workout| workout.phase(0)) {
+        cli.countdown = Some(first.duration);
+        cli.label = Some(first.name.clone());
+    }
+
+    // A preset's colors are chosen to go together, so only pick a contrasting color for the
+    // one that wasn't given when there is no preset to take it from
+    let theme = match cli.theme.as_deref().map(|name| find_theme(&config, name)) {
+        Some(preset) => Theme {
+            fg: cli.fg.unwrap_or(preset.fg),
+            bg: cli.bg.unwrap_or(preset.bg),
+            ..preset
+        },
+        None => Theme::default().with_auto_contrast(cli.fg, cli.bg),
+    };
     let theme = Theme {
-        fg: cli.fg.unwrap_or(default_theme.fg),
-        bg: cli.bg.unwrap_or(default_theme.bg),
+        alarm: cli.alarm_color.unwrap_or(theme.alarm),
+        ..theme
+    };
+
+    let now = Local::now();
+    let alarms: Vec<SystemTime> = cli
+        .alarm
+        .iter()
+        .map(|&time| next_occurrence(time, now))
+        .collect();
+    let until_alarm = alarms
+        .iter()
+        .min()
+        .map(|alarm| alarm.duration_since(now.into()).unwrap_or_default());
+
+    let mode = match (cli.countdown.or(until_alarm), cli.since) {
+        (Some(duration), _) => Mode::Countdown(duration),
+        (None, Some(since)) => Mode::Since(since),
+        (None, None) if cli.clock => Mode::Clock,
+        (None, None) => Mode::Stopwatch,
+    };
+
+    let precision = if cli.hm {
+        Precision::Minutes
+    } else if cli.tenths {
+        Precision::Tenths
+    } else if cli.no_ms {
+        Precision::Seconds
+    } else {
+        cli.subsecond
+    };
+
+    let mut digit_modifier = Modifier::empty();
+    // The flags can only turn a style on, so either they or the config file can ask for it
+    digit_modifier.set(Modifier::BOLD, cli.bold || config.bold);
+    digit_modifier.set(Modifier::ITALIC, cli.italic || config.italic);
+
+    // Timers start paused unless asked otherwise; a quiet countdown has no way to be started
+    // later so it always starts running, and neither would an alarm be much use paused. `clap`
+    // rejects `--paused` alongside any of them.
+    let running = cli.start || cli.quiet || !alarms.is_empty();
+
+    if cli.export_code {
+        println!("{}", export_setup(&cli, theme).encode());
+        return Ok(());
+    }
+
+    if cli.dry_run {
+        print!(
+            "{}",
+            resolved_config(&cli, &config, theme, mode, precision, running)?
+        );
+        return Ok(());
+    }
+
+    let timer = Timer::new(mode)
+        .with_label(cli.label.clone())
+        .with_elapsed(cli.elapsed.unwrap_or_default())
+        .with_reset_to_seed(cli.reset_to_seed)
+        .with_auto_pause(cli.auto_pause)
+        .with_target(cli.target)
+        .with_auto_lap(cli.auto_lap)
+        .with_tick_last(cli.tick_last)
+        .with_lead_in(cli.lead_in.unwrap_or_default())
+        .with_then_stopwatch(cli.then_stopwatch)
+        .with_running(running);
+
+    let segments = cli
+        .segment
+        .into_iter()
+        .map(|segment| {
+            segment
+                .with_auto_pause(cli.auto_pause)
+                .with_auto_lap(cli.auto_lap)
+                .with_tick_last(cli.tick_last)
+                .with_lead_in(cli.lead_in.unwrap_or_default())
+                .with_then_stopwatch(cli.then_stopwatch)
+                .with_running(running)
+        })
+        .collect();
+
+    let mut timers = Timers::default()
+        .with_timer(timer)
+        .with_timers(segments)
+        .with_theme(theme)
+        .with_title_position(cli.title_position)
+        .with_digit_size(cli.pixel_size)
+        .with_align(cli.align)
+        .with_fixed_width(cli.fixed_width)
+        .with_numerals(cli.numerals)
+        .with_collapsed(cli.collapsed)
+        .with_digit_modifier(digit_modifier)
+        .with_rotation(cli.rotate)
+        .with_mirror(cli.mirror)
+        .with_precision(precision)
+        .with_round(cli.round)
+        .with_days(cli.days)
+        .with_focus(cli.focus)
+        .with_sweep(cli.sweep)
+        .with_watermark(cli.watermark)
+        .with_percent(cli.percent)
+        .with_show_paused(cli.show_paused)
+        .with_lap_chart(cli.lap_chart)
+        .with_splits(cli.splits.clone())
+        .with_pie(cli.pie)
+        .with_pulse(cli.pulse)
+        .with_halfway(cli.halfway)
+        .with_set_title(cli.set_title)
+        .with_halfway_bell(cli.halfway_bell)
+        .with_alarm_effect(cli.alarm_effect)
+        .with_keep_awake(cli.keep_awake)
+        .with_idle_exit(cli.idle_exit)
+        .with_alarms(alarms)
+        .with_sounds(config.sounds.clone())
+        .with_exit_on_finish(cli.exit_on_finish)
+        .with_grace(cli.grace);
+    if let Some(fps) = cli.fps {
+        timers = timers.with_fps(fps);
+    }
+
+    // Finish actions run in this order, each independently of whether the others worked
+    if cli.bell {
+        timers = timers.with_completion_action(CompletionAction::Bell);
+    }
+    if cli.notify {
+        let message = match &cli.label {
+            Some(label) => format!("{label} finished"),
+            None => "Countdown finished".to_string(),
+        };
+        timers = timers.with_completion_action(CompletionAction::Notify(message));
+    }
+    if let Some(command) = cli.on_finish {
+        timers = timers.with_completion_action(CompletionAction::Command(command));
+    }
+    if let Some(path) = cli.sound {
+        timers = timers.with_completion_action(CompletionAction::Sound(path));
+    }
+    if let Some(workout) = workout {
+        timers = timers.with_workout(workout);
+    }
+    if cli.banners {
+        timers = timers.with_banners(Banners {
+            go: cli.go_text.clone(),
+            done: cli.done_text.clone(),
+            go_for: cli.go_for,
+        });
+    }
+    if let Some(dir) = session::slots_dir() {
+        timers = timers.with_slots(dir);
+    }
+
+    let session_path = if cli.persist || cli.fresh {
+        session::default_path()
+    } else {
+        None
+    };
+    if let Some(path) = &session_path {
+        if cli.fresh {
+            Session::clear(path)?;
+        } else if let Some(session) = Session::load(path)? {
+            timers = timers.with_session(session);
+        }
+    }
+
+    if cli.attach {
+        let (control, state) = daemon_paths()?;
+        // A freshly spawned background process picks up the handoff left for it directly
+        let handoff = if cli.daemon_child {
+            daemon::take(&state)?.ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::NotFound, "nothing was handed over")
+            })?
+        } else {
+            daemon::request_handoff(&control, &state)?
+        };
+        timers = timers.with_handoff(handoff);
+    }
+
+    // Dropped, and so shut down, when `main` returns
+    let _server = match &cli.serve {
+        Some(addr) => {
+            let status = Arc::new(Mutex::new(timers.summary()));
+            timers = timers.with_status(Arc::clone(&status));
+            Some(StatusServer::start(addr, status)?)
+        }
+        None => None,
+    };
+
+    // Removed from the filesystem when dropped at the end of `main`
+    let _control_pipe = match &cli.control_pipe {
+        Some(path) => {
+            let (pipe, commands) = ControlPipe::create(path)?;
+            timers = timers.with_commands(commands);
+            Some(pipe)
+        }
+        None => None,
     };
 
-    let mut terminal = tui::init()?;
-    let mut timers = Timers::default().with_theme(theme);
-    let timers_result = timers.run(&mut terminal);
-    tui::restore()?;
-    timers_result
+    if cli.daemon_child {
+        let (control, state) = daemon_paths()?;
+        if let Some(dir) = control.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let (pipe, commands) = ControlPipe::create(&control)?;
+        timers = timers.with_commands(commands);
+        let result = timers.run_headless(&state);
+        // The pipe goes only once the last handoff is written, which is what attaching waits for
+        drop(pipe);
+        return result;
+    }
+
+    if cli.quiet {
+        timers.run_quiet()?;
+    } else {
+        let screen = if cli.no_altscreen {
+            Screen::Inline
+        } else {
+            Screen::Alternate
+        };
+        let mut terminal = tui::init(screen)?;
+        let timers_result = timers.run(&mut terminal);
+        tui::release(&mut terminal)?;
+        timers_result?;
+
+        if timers.is_detached() {
+            let (control, state) = daemon_paths()?;
+            if daemon::is_running(&control) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    "a timer is already running in the background, so this one can't be detached",
+                ));
+            }
+            timers.handoff().save(&state)?;
+            spawn_daemon(true)?;
+            println!("Timer running in the background; pick it up with `timers --attach`");
+            return Ok(());
+        }
+        if let (true, Some(path)) = (cli.persist, &session_path) {
+            timers.session().save(path)?;
+        }
+    }
+
+    if let Some(path) = &cli.export_laps {
+        std::fs::write(path, timers.laps_csv())?;
+    }
+    if cli.json {
+        println!("{}", serde_json::to_string(&timers.summary())?);
+    } else if let Some(format) = &cli.format {
+        println!("{}", format.format(timers.timer()));
+    } else if let Some(overrun) = timers.overrun_summary() {
+        println!("{overrun}");
+    }
+    Ok(())
 }
 
-fn center_vertical(area: Rect, height: u16) -> Rect {
-    let [area] = Layout::vertical([Constraint::Length(height)])
-        .flex(Flex::Center)
-        .areas(area);
-    area
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dry_run_shows_the_timer_and_run_settings() {
+        let cli = Cli::parse_from([
+            "timers",
+            "--dry-run",
+            "--lead-in",
+            "5s",
+            "--tick-last",
+            "3",
+            "--exit-on-finish",
+            "--grace",
+            "0s",
+            "--format",
+            "%M:%S",
+        ]);
+        let toml = resolved_config(
+            &cli,
+            &Config::default(),
+            Theme::default(),
+            Mode::Stopwatch,
+            cli.subsecond,
+            false,
+        )
+        .unwrap();
+        let resolved: toml::Table = toml::from_str(&toml).unwrap();
+
+        assert_eq!(resolved["timer"]["lead_in"].as_str(), Some("5s"));
+        assert_eq!(resolved["timer"]["tick_last"].as_integer(), Some(3));
+        assert_eq!(resolved["timer"]["then_stopwatch"].as_bool(), Some(false));
+        assert_eq!(resolved["run"]["exit_on_finish"].as_bool(), Some(true));
+        assert_eq!(resolved["run"]["grace"].as_str(), Some("0ns"));
+        assert_eq!(resolved["run"]["format"].as_str(), Some("%M:%S"));
+        assert!(!resolved["run"].as_table().unwrap().contains_key("serve"));
+    }
 }
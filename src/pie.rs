@@ -0,0 +1,97 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    widgets::Widget,
+};
+use std::f64::consts::TAU;
+
+/// A disc filled clockwise from the top to show how far through something is
+pub(crate) struct Pie {
+    /// How much of the disc is filled, from 0 to 1
+    fraction: f64,
+    style: Style,
+}
+
+impl Pie {
+    pub(crate) fn new(fraction: f64) -> Self {
+        Pie {
+            fraction: fraction.clamp(0.0, 1.0),
+            style: Style::new(),
+        }
+    }
+
+    pub(crate) fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// The columns a pie `rows` high takes up to look round, cells being about twice as tall as
+    /// they are wide
+    pub(crate) fn width(rows: u16) -> u16 {
+        rows.saturating_mul(2)
+    }
+}
+
+impl Widget for Pie {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // Measured in rows, with columns counting for half a row each
+        let radius = f64::from(area.height.min(area.width / 2)) / 2.0;
+        if radius <= 0.0 {
+            return;
+        }
+        let (center_x, center_y) = (f64::from(area.width) / 4.0, f64::from(area.height) / 2.0);
+        for y in 0..area.height {
+            for x in 0..area.width {
+                let dx = (f64::from(x) + 0.5) / 2.0 - center_x;
+                let dy = f64::from(y) + 0.5 - center_y;
+                if dx.hypot(dy) > radius {
+                    continue;
+                }
+                // Clockwise from twelve o'clock, with y growing downwards
+                let angle = dx.atan2(-dy).rem_euclid(TAU);
+                let (symbol, style) = if angle < self.fraction * TAU {
+                    ("█", self.style)
+                } else {
+                    ("░", self.style.add_modifier(Modifier::DIM))
+                };
+                buf[(area.x + x, area.y + y)]
+                    .set_symbol(symbol)
+                    .set_style(style);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The symbol in every cell of a small pie, row by row
+    fn cells(fraction: f64) -> Vec<Vec<String>> {
+        let area = Rect::new(0, 0, 8, 4);
+        let mut buf = Buffer::empty(area);
+        Pie::new(fraction).render(area, &mut buf);
+        (0..area.height)
+            .map(|y| {
+                (0..area.width)
+                    .map(|x| buf[(x, y)].symbol().to_string())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fills_clockwise_from_the_top() {
+        let half = cells(0.5);
+        // The right half is filled and the left half isn't
+        for row in &half {
+            let (left, right) = row.split_at(row.len() / 2);
+            assert!(left.iter().all(|cell| cell != "█"), "{half:?}");
+            assert!(right.iter().all(|cell| cell != "░"), "{half:?}");
+        }
+        assert!(cells(1.0).iter().flatten().all(|cell| cell != "░"));
+        assert!(cells(0.0).iter().flatten().all(|cell| cell != "█"));
+        assert!(cells(0.0).iter().flatten().any(|cell| cell == "░"));
+    }
+}
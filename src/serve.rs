@@ -0,0 +1,116 @@
+use crate::session::SessionSummary;
+use std::{
+    io::{self, ErrorKind, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// How long the listener sleeps between checking for new connections and for shutdown
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Answers every TCP connection with the latest [`SessionSummary`] as JSON, then closes it
+///
+/// The summary is shared with the app through a mutex that the app keeps up to date. The
+/// listener thread is stopped and joined when the server is dropped.
+pub struct StatusServer {
+    local_addr: SocketAddr,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl StatusServer {
+    /// Start listening on `addr` in a background thread
+    pub fn start(addr: impl ToSocketAddrs, status: Arc<Mutex<SessionSummary>>) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        // Non-blocking so the thread can notice when it is asked to shut down
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let handle = thread::spawn({
+            let shutdown = Arc::clone(&shutdown);
+            move || {
+                while !shutdown.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            // A client that goes away early is its own problem
+                            let _ = respond(stream, &status);
+                        }
+                        Err(error) if error.kind() == ErrorKind::WouldBlock => {
+                            thread::sleep(POLL_INTERVAL);
+                        }
+                        Err(_) => thread::sleep(POLL_INTERVAL),
+                    }
+                }
+            }
+        });
+
+        Ok(StatusServer {
+            local_addr,
+            shutdown,
+            handle: Some(handle),
+        })
+    }
+
+    /// The address actually being listened on, useful when binding to port 0
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Drop for StatusServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn respond(mut stream: TcpStream, status: &Mutex<SessionSummary>) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    let summary = status
+        .lock()
+        .map_err(|_| io::Error::other("status lock poisoned"))?
+        .clone();
+    serde_json::to_writer(&mut stream, &summary)?;
+    stream.write_all(b"\n")?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::TimerSummary;
+    use std::io::Read;
+
+    #[test]
+    fn each_connection_gets_the_current_summary() {
+        let status = Arc::new(Mutex::new(SessionSummary::default()));
+        let server = StatusServer::start("127.0.0.1:0", Arc::clone(&status)).unwrap();
+
+        status.lock().unwrap().timers.push(TimerSummary {
+            mode: "countdown".to_string(),
+            value: Duration::from_secs(90),
+            running: true,
+            ..TimerSummary::default()
+        });
+
+        let mut response = String::new();
+        TcpStream::connect(server.local_addr())
+            .unwrap()
+            .read_to_string(&mut response)
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&response).unwrap();
+
+        assert_eq!(json["timers"][0]["mode"], "countdown");
+        assert_eq!(json["timers"][0]["value"]["secs"], 90);
+        assert_eq!(json["timers"][0]["running"], true);
+        drop(server);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::timer::Lap;
+use serde::{Deserialize, Serialize};
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// The parts of a stopwatch that are saved on exit and restored on the next launch
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    /// The elapsed time
+    pub timer: Duration,
+    /// Every recorded lap, oldest first
+    #[serde(default)]
+    pub laps: Vec<Lap>,
+    /// Whether the theme's colors were swapped with the invert key
+    #[serde(default)]
+    pub inverted: bool,
+}
+
+/// A snapshot of every timer, as served to dashboards and scripts by `--serve`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct SessionSummary {
+    pub timers: Vec<TimerSummary>,
+    /// Index into `timers` of the one key presses act on
+    pub selected: usize,
+    /// Whether every timer is being held by pause all
+    pub paused_all: bool,
+}
+
+/// The state of a single timer within a [`SessionSummary`]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimerSummary {
+    pub label: Option<String>,
+    /// `stopwatch`, `countdown`, `since` or `clock`
+    pub mode: String,
+    /// Time elapsed for a stopwatch, time remaining for a countdown
+    pub value: Duration,
+    pub running: bool,
+    pub finished: bool,
+    /// How long the timer has spent paused since it was first started
+    pub paused: Duration,
+    /// Every recorded lap, oldest first
+    pub laps: Vec<Lap>,
+    /// The countdown a stopwatch carried on from with `--then-stopwatch`, if it did
+    pub countdown: Option<Duration>,
+    /// How long it has run over that countdown
+    pub overrun: Option<Duration>,
+}
+
+impl Session {
+    /// Read a saved session, or `None` if nothing has been saved yet
+    pub fn load(path: &Path) -> io::Result<Option<Session>> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// Write the session, creating the parent directory if needed
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents = serde_json::to_string_pretty(self)?;
+        fs::write(path, contents)
+    }
+
+    /// Forget any saved session
+    pub fn clear(path: &Path) -> io::Result<()> {
+        match fs::remove_file(path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The directory saved state lives in: `$XDG_STATE_HOME/timers`, falling back to
+/// `~/.local/state/timers`
+pub fn state_dir() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".local/state"),
+    };
+    Some(base.join("timers"))
+}
+
+/// The directory save slots are kept in, one file per slot
+pub fn slots_dir() -> Option<PathBuf> {
+    Some(state_dir()?.join("slots"))
+}
+
+/// Where the session is saved when persistence is enabled
+pub fn default_path() -> Option<PathBuf> {
+    Some(state_dir()?.join("session.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn laps_round_trip_through_save_and_load() {
+        let path = env::temp_dir().join(format!("timers-session-{}.json", std::process::id()));
+        let session = Session {
+            timer: Duration::from_millis(95_250),
+            laps: vec![
+                Lap {
+                    value: Duration::from_millis(20_100),
+                    auto: false,
+                },
+                Lap {
+                    value: Duration::from_millis(45_999),
+                    auto: true,
+                },
+                Lap {
+                    value: Duration::from_millis(80_001),
+                    auto: false,
+                },
+            ],
+            inverted: true,
+        };
+
+        session.save(&path).unwrap();
+        let loaded = Session::load(&path).unwrap();
+        Session::clear(&path).unwrap();
+
+        assert_eq!(loaded, Some(session));
+        assert_eq!(Session::load(&path).unwrap(), None);
+    }
+}
@@ -0,0 +1,829 @@
+use crate::session::TimerSummary;
+use chrono::{Local, Timelike};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant, SystemTime};
+
+/// What the timer is measuring
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Mode {
+    /// Count up from zero
+    #[default]
+    Stopwatch,
+    /// Count down from the given duration to zero
+    Countdown(Duration),
+    /// Count up from a moment in wall clock time, staying at zero until it is reached
+    Since(SystemTime),
+    /// Show the local time of day
+    Clock,
+}
+
+impl Mode {
+    /// A short lowercase name for the mode, used in the title and in status output
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Stopwatch => "stopwatch",
+            Mode::Countdown(_) => "countdown",
+            Mode::Since(_) => "since",
+            Mode::Clock => "clock",
+        }
+    }
+}
+
+/// How long it has been since `since`, or zero if it is still in the future
+fn elapsed_since(since: SystemTime) -> Duration {
+    SystemTime::now()
+        .duration_since(since)
+        .unwrap_or(Duration::ZERO)
+}
+
+/// How far through the local day it is
+fn time_of_day() -> Duration {
+    let now = Local::now().time();
+    // A leap second shows up as a nanosecond count past one second, so hold it at the top
+    let nanos = now.nanosecond().min(999_999_999);
+    Duration::new(u64::from(now.num_seconds_from_midnight()), nanos)
+}
+
+/// A recorded split of a [`Timer`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lap {
+    /// The timer's value when the lap was recorded
+    pub value: Duration,
+    /// Whether the lap was recorded by `--auto-lap` rather than by hand
+    #[serde(default)]
+    pub auto: bool,
+}
+
+/// How a [`Timer`] was set up, as opposed to how far it has got, so another process can set up
+/// the same timer to carry on with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimerSettings {
+    /// The value a stopwatch started from
+    pub seed: Duration,
+    pub reset_to_seed: bool,
+    pub auto_pause: Option<Duration>,
+    pub target: Option<Duration>,
+    pub auto_lap: Option<Duration>,
+    pub tick_last: u32,
+    pub lead_in: Duration,
+    pub then_stopwatch: bool,
+}
+
+/// Something noteworthy that happened to a [`Timer`] while it was ticking
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimerEvent {
+    /// A countdown reached zero
+    Finished,
+    /// A stopwatch reached its automatic pause limit
+    AutoPaused,
+    /// A countdown passed one of its final whole seconds
+    FinalSecond,
+    /// A countdown passed the middle of its full duration
+    Halfway,
+    /// The lead-in ran out and the timer itself started
+    LeadInOver,
+}
+
+/// A single stopwatch or countdown
+#[derive(Clone, Debug, Default)]
+pub struct Timer {
+    mode: Mode,
+    /// Time elapsed for a stopwatch, time remaining for a countdown
+    value: Duration,
+    /// The value a stopwatch started from
+    seed: Duration,
+    reset_to_seed: bool,
+    running: bool,
+    /// Every recorded lap, oldest first
+    laps: Vec<Lap>,
+    label: Option<String>,
+    finished_at: Option<Instant>,
+    /// A stopwatch pauses itself on reaching this value
+    auto_pause: Option<Duration>,
+    /// The value a stopwatch is working towards, for showing progress
+    target: Option<Duration>,
+    /// A lap is recorded automatically every time this much more has been measured
+    auto_lap: Option<Duration>,
+    /// How many of a countdown's final whole seconds raise [`TimerEvent::FinalSecond`]
+    tick_last: u32,
+    /// Whether the timer has run since it was last reset, so time paused before then isn't
+    /// counted
+    started: bool,
+    /// How long the timer has spent paused since it was first started
+    paused_for: Duration,
+    /// How long is counted down before the timer itself starts
+    lead_in: Duration,
+    /// How much of the lead-in is still to go
+    lead_in_left: Duration,
+    /// Carry on as a stopwatch of the time over when a countdown finishes
+    then_stopwatch: bool,
+    /// The countdown a stopwatch took over from when it finished, while measuring the overrun
+    overrun_of: Option<Duration>,
+}
+
+impl Timer {
+    pub fn new(mode: Mode) -> Self {
+        let value = match mode {
+            Mode::Stopwatch => Duration::ZERO,
+            Mode::Countdown(duration) => duration,
+            Mode::Since(since) => elapsed_since(since),
+            Mode::Clock => time_of_day(),
+        };
+        Timer {
+            mode,
+            value,
+            ..Timer::default()
+        }
+    }
+
+    pub fn with_label(mut self, label: Option<String>) -> Self {
+        self.label = label;
+        self
+    }
+
+    pub fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
+    /// Start a stopwatch from `elapsed` instead of zero
+    pub fn with_elapsed(mut self, elapsed: Duration) -> Self {
+        if self.mode == Mode::Stopwatch {
+            self.seed = elapsed;
+            self.value = elapsed;
+        }
+        self
+    }
+
+    /// Make resetting a stopwatch return to the value given to [`Timer::with_elapsed`]
+    pub fn with_reset_to_seed(mut self, reset_to_seed: bool) -> Self {
+        self.reset_to_seed = reset_to_seed;
+        self
+    }
+
+    pub fn with_running(mut self, running: bool) -> Self {
+        self.running = running;
+        self.started |= running;
+        self
+    }
+
+    /// Pause a stopwatch once it reaches `limit`, so a forgotten one doesn't run forever
+    pub fn with_auto_pause(mut self, limit: Option<Duration>) -> Self {
+        self.auto_pause = limit;
+        self
+    }
+
+    /// Give a stopwatch a value to show progress towards
+    pub fn with_target(mut self, target: Option<Duration>) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Record a lap every `interval`, alongside any recorded by hand
+    pub fn with_auto_lap(mut self, interval: Option<Duration>) -> Self {
+        self.auto_lap = interval.filter(|interval| !interval.is_zero());
+        self
+    }
+
+    /// Mark each of a countdown's last `seconds` whole seconds with [`TimerEvent::FinalSecond`]
+    pub fn with_tick_last(mut self, seconds: u32) -> Self {
+        self.tick_last = seconds;
+        self
+    }
+
+    /// Count down `lead_in` before the stopwatch or countdown itself starts, and again after
+    /// every reset
+    pub fn with_lead_in(mut self, lead_in: Duration) -> Self {
+        if !self.is_wall_clock() {
+            self.lead_in = lead_in;
+            self.lead_in_left = lead_in;
+        }
+        self
+    }
+
+    /// Keep a countdown running once it finishes, turned into a stopwatch of how long it has
+    /// overrun
+    pub fn with_then_stopwatch(mut self, then_stopwatch: bool) -> Self {
+        self.then_stopwatch = then_stopwatch;
+        self
+    }
+
+    /// The countdown this timer finished, and how long it has run over since, while it is
+    /// counting the overrun
+    pub fn overrun(&self) -> Option<(Duration, Duration)> {
+        Some((self.overrun_of?, self.value))
+    }
+
+    /// How long is counted down before the timer itself starts
+    pub fn lead_in(&self) -> Duration {
+        self.lead_in
+    }
+
+    /// How much of the lead-in will still be left once another `elapsed` has passed, or `None`
+    /// once it has run out
+    pub fn lead_in_after(&self, elapsed: Duration) -> Option<Duration> {
+        let elapsed = if self.running {
+            elapsed
+        } else {
+            Duration::ZERO
+        };
+        self.lead_in_left
+            .checked_sub(elapsed)
+            .filter(|left| !left.is_zero())
+    }
+
+    /// Pick up a stopwatch where it was left, along with its laps
+    pub fn with_progress(mut self, value: Duration, laps: Vec<Lap>) -> Self {
+        if self.mode == Mode::Stopwatch {
+            self.value = value;
+            self.laps = laps;
+        }
+        self
+    }
+
+    /// Everything the timer was set up with, for [`Timer::with_settings`] to set up again
+    pub fn settings(&self) -> TimerSettings {
+        TimerSettings {
+            seed: self.seed,
+            reset_to_seed: self.reset_to_seed,
+            auto_pause: self.auto_pause,
+            target: self.target,
+            auto_lap: self.auto_lap,
+            tick_last: self.tick_last,
+            lead_in: self.lead_in,
+            then_stopwatch: self.then_stopwatch,
+        }
+    }
+
+    /// Set the timer up the same as the one `settings` were taken from, before it has started
+    pub fn with_settings(self, settings: TimerSettings) -> Self {
+        self.with_elapsed(settings.seed)
+            .with_reset_to_seed(settings.reset_to_seed)
+            .with_auto_pause(settings.auto_pause)
+            .with_target(settings.target)
+            .with_auto_lap(settings.auto_lap)
+            .with_tick_last(settings.tick_last)
+            .with_lead_in(settings.lead_in)
+            .with_then_stopwatch(settings.then_stopwatch)
+    }
+
+    /// Carry on from where another process left off, as given by its summary of the timer and
+    /// how much of the lead-in it still had to go
+    pub(crate) fn with_handed_over(
+        mut self,
+        summary: TimerSummary,
+        lead_in_left: Duration,
+    ) -> Self {
+        if self.is_wall_clock() {
+            return self;
+        }
+        // A countdown carried on as a stopwatch measures the overrun from where it finished
+        if let Some(countdown) = summary.countdown {
+            self.mode = Mode::Stopwatch;
+            self.overrun_of = Some(countdown);
+        }
+        self.value = summary.value;
+        self.laps = summary.laps;
+        self.paused_for = summary.paused;
+        self.started |= !summary.paused.is_zero() || self.measured() != Some(Duration::ZERO);
+        self.lead_in_left = lead_in_left.min(self.lead_in);
+        self
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Time elapsed for a stopwatch, time remaining for a countdown
+    pub fn value(&self) -> Duration {
+        self.value
+    }
+
+    /// Every recorded lap, oldest first
+    pub fn laps(&self) -> &[Lap] {
+        &self.laps
+    }
+
+    /// How long the lap at `index` took, measured from the one before it or from the start
+    pub fn lap_time(&self, index: usize) -> Duration {
+        let previous = match index.checked_sub(1) {
+            Some(previous) => self.laps[previous].value,
+            None => self.start_value(),
+        };
+        self.laps[index].value.abs_diff(previous)
+    }
+
+    /// How long the lap in progress has taken so far, with the timer at `value`
+    pub fn current_lap_time(&self, value: Duration) -> Duration {
+        let previous = self.laps.last().map_or(self.start_value(), |lap| lap.value);
+        value.abs_diff(previous)
+    }
+
+    /// The index of the quickest lap, once there are at least two to compare
+    pub fn best_lap(&self) -> Option<usize> {
+        if self.laps.len() < 2 {
+            return None;
+        }
+        (0..self.laps.len()).min_by_key(|&index| self.lap_time(index))
+    }
+
+    /// The value the first lap is measured from
+    fn start_value(&self) -> Duration {
+        match self.mode {
+            Mode::Countdown(duration) => duration,
+            _ => Duration::ZERO,
+        }
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// How long the timer has spent paused since it was first started or last reset
+    pub fn paused_for(&self) -> Duration {
+        self.paused_for
+    }
+
+    /// Count `elapsed` as time spent paused, if the timer has been started and isn't finished
+    pub fn hold(&mut self, elapsed: Duration) {
+        if self.started && !self.is_finished() && !self.is_wall_clock() {
+            self.paused_for += elapsed;
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self.mode, Mode::Countdown(_)) && self.value.is_zero()
+    }
+
+    /// When the countdown reached zero, if it has since it was last reset
+    pub fn finished_at(&self) -> Option<Instant> {
+        self.finished_at
+    }
+
+    /// How far through the countdown, or towards the stopwatch's target, `value` is, from 0 to 1
+    pub fn progress(&self, value: Duration) -> Option<f64> {
+        let (done, total) = match (self.mode, self.target) {
+            (Mode::Countdown(duration), _) => (duration.saturating_sub(value), duration),
+            (Mode::Stopwatch, Some(target)) => (value, target),
+            _ => return None,
+        };
+        if total.is_zero() {
+            return Some(1.0);
+        }
+        Some((done.as_secs_f64() / total.as_secs_f64()).clamp(0.0, 1.0))
+    }
+
+    /// Whether the value comes from the wall clock rather than from being started and stopped
+    pub fn is_wall_clock(&self) -> bool {
+        matches!(self.mode, Mode::Since(_) | Mode::Clock)
+    }
+
+    pub fn toggle(&mut self) {
+        if self.is_finished() || self.is_wall_clock() {
+            return;
+        }
+        self.running = !self.running;
+        self.started |= self.running;
+    }
+
+    pub fn reset(&mut self) {
+        if let Some(duration) = self.overrun_of.take() {
+            self.mode = Mode::Countdown(duration);
+        }
+        self.value = match self.mode {
+            Mode::Stopwatch if self.reset_to_seed => self.seed,
+            Mode::Stopwatch => Duration::new(0, 0),
+            Mode::Countdown(duration) => duration,
+            Mode::Since(since) => elapsed_since(since),
+            Mode::Clock => time_of_day(),
+        };
+        self.laps.clear();
+        self.finished_at = None;
+        self.started = self.running;
+        self.paused_for = Duration::ZERO;
+        self.lead_in_left = self.lead_in;
+    }
+
+    /// Turn a stopwatch into a countdown or back again, keeping whether it is running
+    ///
+    /// A stopwatch becomes a countdown of the time it has measured, so it counts back down to
+    /// zero, and resetting it returns to that time. A countdown becomes a stopwatch of the time
+    /// that has passed since it started. Laps are cleared in both directions. A stopwatch that
+    /// hasn't measured anything yet and timers following the wall clock are left alone.
+    pub fn switch_mode(&mut self) {
+        let (mode, value) = match self.mode {
+            Mode::Stopwatch if self.value.is_zero() => return,
+            Mode::Stopwatch => (Mode::Countdown(self.value), self.value),
+            Mode::Countdown(duration) => (Mode::Stopwatch, duration.saturating_sub(self.value)),
+            Mode::Since(_) | Mode::Clock => return,
+        };
+        self.mode = mode;
+        self.value = value;
+        self.overrun_of = None;
+        self.seed = Duration::ZERO;
+        self.laps.clear();
+        self.finished_at = None;
+    }
+
+    /// Put a stopwatch at `value` elapsed or a countdown at `value` remaining, leaving its laps
+    /// and whether it is running alone
+    pub fn set_value(&mut self, value: Duration) {
+        if self.is_wall_clock() {
+            return;
+        }
+        self.value = value;
+        if !self.is_finished() {
+            self.finished_at = None;
+        }
+    }
+
+    /// Start counting down `duration` afresh, running, as the next of several alarms
+    ///
+    /// When the last countdown finished is left alone, so an alarm effect still plays out.
+    pub fn restart_countdown(&mut self, duration: Duration) {
+        self.mode = Mode::Countdown(duration);
+        self.overrun_of = None;
+        self.value = duration;
+        self.laps.clear();
+        self.running = true;
+        self.started = true;
+        self.paused_for = Duration::ZERO;
+    }
+
+    /// Make the timer a countdown of `duration` from the next reset, leaving it alone until then
+    pub fn set_countdown(&mut self, duration: Duration) {
+        self.mode = Mode::Countdown(duration);
+        self.overrun_of = None;
+    }
+
+    /// Record the current value as a lap
+    pub fn lap(&mut self) {
+        if !self.is_wall_clock() {
+            self.laps.push(Lap {
+                value: self.value,
+                auto: false,
+            });
+        }
+    }
+
+    /// Whether the timer moves forward on [`Timer::tick`]
+    pub fn is_advancing(&self) -> bool {
+        self.running || self.is_wall_clock()
+    }
+
+    /// Advance the timer by `elapsed` if it is running, returning anything noteworthy that
+    /// happened along the way
+    pub fn tick(&mut self, elapsed: Duration) -> Vec<TimerEvent> {
+        let mut events = Vec::new();
+        // Time spent on the lead-in doesn't count towards the timer itself
+        let elapsed = if self.running && !self.lead_in_left.is_zero() {
+            let lead_in = elapsed.min(self.lead_in_left);
+            self.lead_in_left -= lead_in;
+            if self.lead_in_left.is_zero() {
+                events.push(TimerEvent::LeadInOver);
+            }
+            elapsed - lead_in
+        } else {
+            elapsed
+        };
+        let measured_before = self.measured();
+        match self.mode {
+            Mode::Since(since) => self.value = elapsed_since(since),
+            Mode::Clock => self.value = time_of_day(),
+            _ if !self.running => self.hold(elapsed),
+            Mode::Stopwatch => {
+                let before = self.value;
+                self.value += elapsed;
+                if let Some(limit) = self.auto_pause {
+                    if before < limit && self.value >= limit {
+                        self.value = limit;
+                        self.running = false;
+                        events.push(TimerEvent::AutoPaused);
+                    }
+                }
+            }
+            Mode::Countdown(duration) => {
+                let before = self.value;
+                self.value = self.value.saturating_sub(elapsed);
+                let half = duration / 2;
+                if self.value <= half && half < before {
+                    events.push(TimerEvent::Halfway);
+                }
+                // Several seconds can pass in one slow frame, and each of them gets its own event
+                let final_seconds = (1..=u64::from(self.tick_last))
+                    .map(Duration::from_secs)
+                    .filter(|&second| self.value <= second && second < before)
+                    .count();
+                events.extend(std::iter::repeat_n(TimerEvent::FinalSecond, final_seconds));
+                if self.value.is_zero() && self.then_stopwatch {
+                    // Whatever of this tick went past zero already counts as overrun
+                    self.mode = Mode::Stopwatch;
+                    self.value = elapsed.saturating_sub(before);
+                    self.overrun_of = Some(duration);
+                    self.finished_at = Some(Instant::now());
+                    events.push(TimerEvent::Finished);
+                } else if self.value.is_zero() {
+                    self.running = false;
+                    self.finished_at = Some(Instant::now());
+                    events.push(TimerEvent::Finished);
+                }
+            }
+        }
+        if let (Some(before), Some(after)) = (measured_before, self.measured()) {
+            self.record_auto_laps(before, after);
+        }
+        events
+    }
+
+    /// How much time the timer has measured since it started, for stopwatches and countdowns
+    fn measured(&self) -> Option<Duration> {
+        self.measured_at(self.value)
+    }
+
+    /// How much time the timer has measured since it started once it shows `value`, for
+    /// stopwatches and countdowns
+    pub fn measured_at(&self, value: Duration) -> Option<Duration> {
+        match self.mode {
+            Mode::Stopwatch => Some(value),
+            Mode::Countdown(duration) => Some(duration.saturating_sub(value)),
+            Mode::Since(_) | Mode::Clock => None,
+        }
+    }
+
+    /// Record an automatic lap at every interval boundary passed going from `before` measured
+    /// to `after`, however many that is, so a slow frame doesn't skip any
+    fn record_auto_laps(&mut self, before: Duration, after: Duration) {
+        let Some(interval) = self.auto_lap else {
+            return;
+        };
+        let first = before.as_nanos() / interval.as_nanos() + 1;
+        let last = after.as_nanos() / interval.as_nanos();
+        for boundary in first..=last {
+            let Some(measured) = u32::try_from(boundary)
+                .ok()
+                .and_then(|boundary| interval.checked_mul(boundary))
+            else {
+                break;
+            };
+            let value = match self.mode {
+                Mode::Countdown(duration) => duration.saturating_sub(measured),
+                _ => measured,
+            };
+            self.laps.push(Lap { value, auto: true });
+        }
+    }
+
+    /// The value as it will be once another `elapsed` has passed, without changing the timer
+    pub fn value_after(&self, elapsed: Duration) -> Duration {
+        match self.mode {
+            Mode::Since(since) => elapsed_since(since),
+            Mode::Clock => time_of_day(),
+            _ if !self.running => self.value,
+            _ if elapsed <= self.lead_in_left => self.value,
+            Mode::Stopwatch => {
+                let elapsed = elapsed - self.lead_in_left;
+                let value = self.value + elapsed;
+                self.auto_pause
+                    .map_or(value, |limit| value.min(limit.max(self.value)))
+            }
+            Mode::Countdown(_) => self.value.saturating_sub(elapsed - self.lead_in_left),
+        }
+    }
+}
+
+/// The timers being shown and which one key presses act on; there is always at least one
+#[derive(Debug)]
+pub(crate) struct TimerList {
+    timers: Vec<Timer>,
+    selected: usize,
+}
+
+impl Default for TimerList {
+    fn default() -> Self {
+        TimerList {
+            timers: vec![Timer::default()],
+            selected: 0,
+        }
+    }
+}
+
+impl TimerList {
+    /// Replace the timers, keeping the current one if `timers` is empty
+    pub(crate) fn replace(&mut self, timers: Vec<Timer>) {
+        if !timers.is_empty() {
+            self.timers = timers;
+            self.selected = 0;
+        }
+    }
+
+    pub(crate) fn all(&self) -> &[Timer] {
+        &self.timers
+    }
+
+    pub(crate) fn all_mut(&mut self) -> &mut [Timer] {
+        &mut self.timers
+    }
+
+    pub(crate) fn selected_index(&self) -> usize {
+        self.selected
+    }
+
+    pub(crate) fn selected(&self) -> &Timer {
+        &self.timers[self.selected]
+    }
+
+    pub(crate) fn selected_mut(&mut self) -> &mut Timer {
+        &mut self.timers[self.selected]
+    }
+
+    /// Select the timer at `index`, if there is one
+    pub(crate) fn select(&mut self, index: usize) {
+        if index < self.timers.len() {
+            self.selected = index;
+        }
+    }
+
+    pub(crate) fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.timers.len();
+    }
+
+    pub(crate) fn select_previous(&mut self) {
+        self.selected = (self.selected + self.timers.len() - 1) % self.timers.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_laps_are_not_skipped_by_a_long_frame() {
+        let mut timer = Timer::new(Mode::Stopwatch)
+            .with_auto_lap(Some(Duration::from_secs(60)))
+            .with_running(true);
+        timer.tick(Duration::from_secs(50));
+        timer.lap();
+        timer.tick(Duration::from_secs(150));
+
+        let laps: Vec<(u64, bool)> = timer
+            .laps()
+            .iter()
+            .map(|lap| (lap.value.as_secs(), lap.auto))
+            .collect();
+        assert_eq!(laps, [(50, false), (60, true), (120, true), (180, true)]);
+    }
+
+    #[test]
+    fn pauses_count_from_the_first_start_until_reset() {
+        let mut timer = Timer::new(Mode::Stopwatch);
+        timer.tick(Duration::from_secs(5));
+        assert_eq!(timer.paused_for(), Duration::ZERO);
+
+        timer.toggle();
+        timer.tick(Duration::from_secs(10));
+        timer.toggle();
+        timer.tick(Duration::from_secs(3));
+        assert_eq!(timer.value(), Duration::from_secs(10));
+        assert_eq!(timer.paused_for(), Duration::from_secs(3));
+
+        timer.reset();
+        assert_eq!(timer.value(), Duration::ZERO);
+        assert_eq!(timer.paused_for(), Duration::ZERO);
+    }
+
+    #[test]
+    fn final_seconds_fire_once_for_every_second_crossed() {
+        let mut timer = Timer::new(Mode::Countdown(Duration::from_secs(10)))
+            .with_tick_last(3)
+            .with_running(true);
+        let mut ticks = |elapsed| {
+            timer
+                .tick(Duration::from_millis(elapsed))
+                .into_iter()
+                .filter(|event| *event == TimerEvent::FinalSecond)
+                .count()
+        };
+
+        assert_eq!(ticks(6_500), 0);
+        assert_eq!(ticks(500), 1);
+        assert_eq!(ticks(400), 0);
+        // A slow frame passing both of the next seconds fires for each of them
+        assert_eq!(ticks(1_600), 2);
+        assert_eq!(ticks(1_000), 0);
+    }
+
+    #[test]
+    fn halfway_fires_once_as_the_middle_is_passed() {
+        let mut timer = Timer::new(Mode::Countdown(Duration::from_secs(10))).with_running(true);
+        let mut halfway = |elapsed| {
+            timer
+                .tick(Duration::from_secs(elapsed))
+                .contains(&TimerEvent::Halfway)
+        };
+
+        assert!(!halfway(4));
+        assert!(halfway(1));
+        assert!(!halfway(1));
+        assert!(!halfway(4));
+    }
+
+    #[test]
+    fn lead_in_runs_before_the_stopwatch_and_again_after_reset() {
+        let mut timer = Timer::new(Mode::Stopwatch)
+            .with_lead_in(Duration::from_secs(3))
+            .with_running(true);
+        assert!(timer.tick(Duration::from_secs(2)).is_empty());
+        assert_eq!(
+            timer.lead_in_after(Duration::ZERO),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(
+            timer.value_after(Duration::from_secs(4)),
+            Duration::from_secs(3)
+        );
+        assert_eq!(timer.value(), Duration::ZERO);
+
+        // The part of a frame after the lead-in ends counts towards the stopwatch
+        assert_eq!(timer.tick(Duration::from_secs(5)), [TimerEvent::LeadInOver]);
+        assert_eq!(timer.lead_in_after(Duration::ZERO), None);
+        assert_eq!(timer.value(), Duration::from_secs(4));
+
+        timer.reset();
+        assert_eq!(
+            timer.lead_in_after(Duration::ZERO),
+            Some(Duration::from_secs(3))
+        );
+    }
+
+    #[test]
+    fn countdowns_reset_to_their_full_duration() {
+        let mut timer = Timer::new(Mode::Countdown(Duration::from_secs(90))).with_running(true);
+        timer.tick(Duration::from_secs(30));
+        timer.lap();
+        timer.reset();
+        assert_eq!(timer.value(), Duration::from_secs(90));
+        assert!(timer.laps().is_empty());
+
+        // Even once it has finished
+        timer.tick(Duration::from_secs(120));
+        assert!(timer.is_finished());
+        timer.reset();
+        assert_eq!(timer.value(), Duration::from_secs(90));
+        assert!(!timer.is_finished());
+        assert_eq!(timer.finished_at(), None);
+    }
+
+    #[test]
+    fn stopwatches_reset_to_zero_unless_kept_at_their_seed() {
+        let seed = Duration::from_secs(600);
+        let mut timer = Timer::new(Mode::Stopwatch)
+            .with_elapsed(seed)
+            .with_running(true);
+        timer.tick(Duration::from_secs(5));
+        timer.reset();
+        assert_eq!(timer.value(), Duration::ZERO);
+
+        let mut timer = Timer::new(Mode::Stopwatch)
+            .with_elapsed(seed)
+            .with_reset_to_seed(true)
+            .with_running(true);
+        timer.tick(Duration::from_secs(5));
+        timer.reset();
+        assert_eq!(timer.value(), seed);
+    }
+
+    #[test]
+    fn a_countdown_switched_from_a_stopwatch_resets_to_the_time_it_was_switched_at() {
+        let mut timer = Timer::new(Mode::Stopwatch).with_running(true);
+        timer.tick(Duration::from_secs(45));
+        timer.switch_mode();
+        timer.tick(Duration::from_secs(10));
+        timer.reset();
+        assert_eq!(timer.mode(), Mode::Countdown(Duration::from_secs(45)));
+        assert_eq!(timer.value(), Duration::from_secs(45));
+    }
+
+    #[test]
+    fn a_finished_countdown_can_carry_on_counting_the_overrun() {
+        let duration = Duration::from_secs(60);
+        let mut timer = Timer::new(Mode::Countdown(duration))
+            .with_then_stopwatch(true)
+            .with_running(true);
+        assert!(timer
+            .tick(Duration::from_secs(61))
+            .contains(&TimerEvent::Finished));
+        assert_eq!(timer.mode(), Mode::Stopwatch);
+        assert!(timer.is_running());
+        timer.tick(Duration::from_secs(4));
+        assert_eq!(timer.overrun(), Some((duration, Duration::from_secs(5))));
+
+        timer.reset();
+        assert_eq!(timer.mode(), Mode::Countdown(duration));
+        assert_eq!(timer.value(), duration);
+        assert_eq!(timer.overrun(), None);
+    }
+}
@@ -0,0 +1,97 @@
+use ratatui::{buffer::Buffer, layout::Rect};
+
+/// The block characters big digits are drawn with at quadrant and smaller sizes, indexed by a
+/// mask of which of their 2x2 pixels are set, reading left to right then top to bottom
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+
+/// The block characters big digits are drawn with at sextant and third height sizes, indexed
+/// like [`QUADRANTS`] but with 2x3 pixels
+const SEXTANTS: [char; 64] = [
+    ' ', '🬀', '🬁', '🬂', '🬃', '🬄', '🬅', '🬆', '🬇', '🬈', '🬉', '🬊', '🬋', '🬌', '🬍', '🬎', '🬏', '🬐', '🬑',
+    '🬒', '🬓', '▌', '🬔', '🬕', '🬖', '🬗', '🬘', '🬙', '🬚', '🬛', '🬜', '🬝', '🬞', '🬟', '🬠', '🬡', '🬢', '🬣',
+    '🬤', '🬥', '🬦', '🬧', '▐', '🬨', '🬩', '🬪', '🬫', '🬬', '🬭', '🬮', '🬯', '🬰', '🬱', '🬲', '🬳', '🬴', '🬵',
+    '🬶', '🬷', '🬸', '🬹', '🬺', '🬻', '█',
+];
+
+/// Flip whatever has been drawn in `area` left to right and/or top to bottom, moving each cell
+/// to its mirrored position and flipping the block character in it to match
+///
+/// Flipping both ways turns the drawing upside down.
+pub(crate) fn flip(buf: &mut Buffer, area: Rect, horizontal: bool, vertical: bool) {
+    let area = area.intersection(buf.area);
+    if !(horizontal || vertical) || area.is_empty() {
+        return;
+    }
+
+    let cells: Vec<_> = area
+        .positions()
+        .map(|position| buf[position].clone())
+        .collect();
+    for position in area.positions() {
+        let mut x = position.x - area.x;
+        let mut y = position.y - area.y;
+        if horizontal {
+            x = area.width - 1 - x;
+        }
+        if vertical {
+            y = area.height - 1 - y;
+        }
+        let mut cell = cells[usize::from(y * area.width + x)].clone();
+        let symbol = cell.symbol().chars().next().unwrap_or(' ');
+        if let Some(flipped) = flip_symbol(symbol, horizontal, vertical) {
+            cell.set_char(flipped);
+        }
+        buf[position] = cell;
+    }
+}
+
+/// The block character with its pixels flipped, or `None` if it isn't one of the characters
+/// big digits are drawn with
+fn flip_symbol(symbol: char, horizontal: bool, vertical: bool) -> Option<char> {
+    // Quadrants come first so the characters both sets share keep their 2x2 meaning, which
+    // flips to the same character either way
+    if let Some(mask) = QUADRANTS.iter().position(|&c| c == symbol) {
+        return Some(QUADRANTS[flip_mask(mask, 2, horizontal, vertical)]);
+    }
+    let mask = SEXTANTS.iter().position(|&c| c == symbol)?;
+    Some(SEXTANTS[flip_mask(mask, 3, horizontal, vertical)])
+}
+
+/// Flip a mask of 2 pixel wide rows, with the top row in the lowest bits
+fn flip_mask(mask: usize, rows: usize, horizontal: bool, vertical: bool) -> usize {
+    let mut flipped = 0;
+    for row in 0..rows {
+        let mut pixels = (mask >> (row * 2)) & 0b11;
+        if horizontal {
+            pixels = (pixels & 1) << 1 | pixels >> 1;
+        }
+        let row = if vertical { rows - 1 - row } else { row };
+        flipped |= pixels << (row * 2);
+    }
+    flipped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glyphs_flip_pixel_for_pixel() {
+        assert_eq!(flip_symbol('▘', true, true), Some('▗'));
+        assert_eq!(flip_symbol('▛', true, false), Some('▜'));
+        assert_eq!(flip_symbol('▀', false, true), Some('▄'));
+        // Top left and middle right of a sextant become middle left and bottom right
+        assert_eq!(flip_symbol('🬈', true, true), Some('🬢'));
+        assert_eq!(flip_symbol('x', true, true), None);
+    }
+
+    #[test]
+    fn odd_width_areas_rotate_around_their_middle_cell() {
+        let area = Rect::new(0, 0, 3, 2);
+        let mut buf = Buffer::with_lines(["▘ ▌", " █ "]);
+        flip(&mut buf, area, true, true);
+        assert_eq!(buf, Buffer::with_lines([" █ ", "▐ ▗"]));
+    }
+}
@@ -1,23 +1,131 @@
-use std::io::{self, stdout, Stdout};
+use std::{
+    io::{self, stderr, stdout, BufWriter, IsTerminal, Write},
+    panic,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
-use crossterm::{execute, terminal::*};
-use ratatui::prelude::*;
+use crossterm::{cursor::Show, execute, terminal::*};
+use ratatui::{prelude::*, TerminalOptions, Viewport};
 
 /// A type alias for the terminal type used in this application
-pub type Tui = Terminal<CrosstermBackend<Stdout>>;
+///
+/// The backend is buffered, as ratatui writes every changed cell separately and the frame is only
+/// sent to the terminal when it is flushed after drawing.
+pub type Tui = Terminal<CrosstermBackend<BufWriter<Output>>>;
+
+/// How many rows the TUI takes up when drawn inline instead of on the alternate screen
+const INLINE_HEIGHT: u16 = 12;
+
+/// Whether [`init`] entered the alternate screen, so [`restore`] knows whether to leave it
+static ALTERNATE_SCREEN: AtomicBool = AtomicBool::new(false);
+
+/// Whether the terminal's title was saved by [`set_title`], so [`restore`] knows to put it back
+static TITLE_SAVED: AtomicBool = AtomicBool::new(false);
+
+/// Whether [`init`] found stderr redirected and fell back to drawing on stdout
+static ON_STDOUT: AtomicBool = AtomicBool::new(false);
+
+/// The stream the TUI is drawn on and the bell is rung on
+///
+/// That is stderr, keeping stdout free for the result printed on exit so it can be piped or
+/// redirected, unless stderr is the one going elsewhere and stdout is still the terminal.
+pub struct Output;
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if ON_STDOUT.load(Ordering::Relaxed) {
+            stdout().write(buf)
+        } else {
+            stderr().write(buf)
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if ON_STDOUT.load(Ordering::Relaxed) {
+            stdout().flush()
+        } else {
+            stderr().flush()
+        }
+    }
+}
+
+/// Where the TUI is drawn
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Screen {
+    /// Take over the whole terminal, leaving the scrollback untouched on exit
+    #[default]
+    Alternate,
+    /// Draw in a few rows of the normal screen, so the last frame stays in the scrollback
+    Inline,
+}
 
 /// Initialize the terminal
-pub fn init() -> io::Result<Tui> {
-    execute!(stdout(), EnterAlternateScreen)?;
+pub fn init(screen: Screen) -> io::Result<Tui> {
+    install_panic_hook();
+    ON_STDOUT.store(
+        !stderr().is_terminal() && stdout().is_terminal(),
+        Ordering::Relaxed,
+    );
+    let viewport = match screen {
+        Screen::Alternate => {
+            execute!(Output, EnterAlternateScreen)?;
+            ALTERNATE_SCREEN.store(true, Ordering::Relaxed);
+            Viewport::Fullscreen
+        }
+        Screen::Inline => Viewport::Inline(INLINE_HEIGHT),
+    };
     enable_raw_mode()?;
-    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+    let mut terminal = Terminal::with_options(
+        CrosstermBackend::new(BufWriter::new(Output)),
+        TerminalOptions { viewport },
+    )?;
     terminal.clear()?;
     Ok(terminal)
 }
 
 /// Restore the terminal to its original state
 pub fn restore() -> io::Result<()> {
-    execute!(stdout(), LeaveAlternateScreen)?;
+    if ALTERNATE_SCREEN.swap(false, Ordering::Relaxed) {
+        execute!(Output, LeaveAlternateScreen)?;
+    }
+    if TITLE_SAVED.swap(false, Ordering::Relaxed) {
+        // Pop the title saved on the terminal's title stack
+        write!(Output, "\x1b[23;0t")?;
+    }
     disable_raw_mode()?;
+    execute!(Output, Show)?;
     Ok(())
 }
+
+/// Set the terminal window's title, saving the one it had the first time so [`restore`] can put
+/// it back
+pub fn set_title(title: &str) -> io::Result<()> {
+    if !TITLE_SAVED.swap(true, Ordering::Relaxed) {
+        // Push the current title onto the terminal's title stack
+        write!(Output, "\x1b[22;0t")?;
+    }
+    execute!(Output, SetTitle(title))
+}
+
+/// Restore the terminal after a normal exit, first moving the cursor under an inline viewport
+/// so the shell prompt doesn't draw over the last frame
+pub fn release(terminal: &mut Tui) -> io::Result<()> {
+    if !ALTERNATE_SCREEN.load(Ordering::Relaxed) {
+        let area = terminal.get_frame().area();
+        terminal.set_cursor_position((0, area.bottom().saturating_sub(1)))?;
+        restore()?;
+        writeln!(Output)?;
+        return Ok(());
+    }
+    restore()
+}
+
+/// Restore the terminal before a panic message is printed, so it is readable and the shell is
+/// left usable
+fn install_panic_hook() {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = restore();
+        hook(info);
+    }));
+}